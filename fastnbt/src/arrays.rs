@@ -8,6 +8,62 @@ pub(crate) const BYTE_ARRAY_TOKEN: &str = "__fastnbt_byte_array";
 pub(crate) const INT_ARRAY_TOKEN: &str = "__fastnbt_int_array";
 pub(crate) const LONG_ARRAY_TOKEN: &str = "__fastnbt_long_array";
 
+/// The elements of an NBT array as big-endian bytes, `N` per element. These
+/// are normally given as bytes, but a deserializer that buffers its input, as
+/// `#[serde(flatten)]` does, may hand them on as a sequence of the elements
+/// instead.
+pub(crate) struct ArrayBytes<const N: usize>(pub(crate) Vec<u8>);
+
+impl<'de, const N: usize> Deserialize<'de> for ArrayBytes<N> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct InnerVisitor<const N: usize>;
+        impl<'de, const N: usize> Visitor<'de> for InnerVisitor<N> {
+            type Value = ArrayBytes<N>;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(formatter, "bytes or a sequence of {}-byte integers", N)
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(ArrayBytes(v.to_vec()))
+            }
+
+            fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(ArrayBytes(v))
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                let bits = N * 8;
+                let mut data = Vec::with_capacity(seq.size_hint().unwrap_or(0) * N);
+                while let Some(n) = seq.next_element::<i64>()? {
+                    // Check the element fits in N bytes by sign extending it back.
+                    if bits < 64 && (n << (64 - bits)) >> (64 - bits) != n {
+                        return Err(serde::de::Error::invalid_value(
+                            serde::de::Unexpected::Signed(n),
+                            &self,
+                        ));
+                    }
+                    data.extend_from_slice(&n.to_be_bytes()[8 - N..]);
+                }
+                Ok(ArrayBytes(data))
+            }
+        }
+        deserializer.deserialize_byte_buf(InnerVisitor::<N>)
+    }
+}
+
 /// NBT ByteArray that owns its data. This type preserves the exact NBT type
 /// when (de)serializing. This dereferences into a i8 slice, so should be usable
 /// basically anywhere a slice should be.
@@ -54,10 +110,10 @@ impl<'de> Deserialize<'de> for ByteArray {
                 let token = map.next_key::<&str>()?.ok_or_else(|| {
                     serde::de::Error::custom("expected NBT byte array token, but got empty map")
                 })?;
-                let data = map.next_value::<ByteBuf>()?;
+                let data = map.next_value::<ArrayBytes<1>>()?;
 
                 if token == BYTE_ARRAY_TOKEN {
-                    Ok(ByteArray::from_buf(data.into_vec()))
+                    Ok(ByteArray::from_buf(data.0))
                 } else {
                     Err(serde::de::Error::custom("expected NBT byte array token"))
                 }
@@ -190,10 +246,10 @@ impl<'de> Deserialize<'de> for IntArray {
                 let token = map.next_key::<&str>()?.ok_or_else(|| {
                     serde::de::Error::custom("expected NBT int array token, but got empty map")
                 })?;
-                let data = map.next_value::<ByteBuf>()?;
+                let data = map.next_value::<ArrayBytes<4>>()?;
 
                 match token {
-                    INT_ARRAY_TOKEN => IntArray::from_bytes(&data)
+                    INT_ARRAY_TOKEN => IntArray::from_bytes(&data.0)
                         .map_err(|_| serde::de::Error::custom("could not read i32 for int array")),
                     _ => Err(serde::de::Error::custom("expected NBT int array token")),
                 }
@@ -288,10 +344,10 @@ impl<'de> Deserialize<'de> for LongArray {
                 let token = map.next_key::<&str>()?.ok_or_else(|| {
                     serde::de::Error::custom("expected NBT long array token, but got empty map")
                 })?;
-                let data = map.next_value::<ByteBuf>()?;
+                let data = map.next_value::<ArrayBytes<8>>()?;
 
                 match token {
-                    LONG_ARRAY_TOKEN => LongArray::from_bytes(&data)
+                    LONG_ARRAY_TOKEN => LongArray::from_bytes(&data.0)
                         .map_err(|_| serde::de::Error::custom("could not read i64 for long array")),
                     _ => Err(serde::de::Error::custom("expected NBT long array token")),
                 }
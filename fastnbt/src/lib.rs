@@ -137,6 +137,8 @@ mod input;
 mod macros;
 
 pub use arrays::*;
+#[cfg(feature = "indexmap")]
+pub use value::OrderedValue;
 pub use value::{from_value, to_value, Value};
 
 #[cfg(test)]
@@ -153,7 +155,7 @@ use std::{
 };
 
 /// An NBT tag. This does not carry the value or the name of the data.
-#[derive(Deserialize, Debug, PartialEq, Clone, Copy)]
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
 #[cfg_attr(feature = "arbitrary1", derive(arbitrary::Arbitrary))]
 #[repr(u8)]
 pub enum Tag {
@@ -2,7 +2,7 @@ use std::borrow::Cow;
 
 use serde::Deserialize;
 
-use crate::{value::from_value, ByteArray, IntArray, LongArray};
+use crate::{value::from_value, ByteArray, IntArray, LongArray, Value};
 
 #[test]
 fn simple_types() {
@@ -95,6 +95,28 @@ fn int_array_types() {
     assert_eq!(expected, val);
 }
 
+#[test]
+fn arrays_from_element_sequences() {
+    // Deserializers that buffer, as for #[serde(flatten)], may give the
+    // elements of an array rather than its bytes.
+    let json = serde_json::json!({
+        "bytes": {"__fastnbt_byte_array": [1, -2]},
+        "ints": {"__fastnbt_int_array": [1, -2]},
+        "longs": {"__fastnbt_long_array": [1, -2]},
+    });
+    let val: Value = serde_json::from_value(json).unwrap();
+    assert_eq!(
+        nbt!({"bytes": [B; 1, -2], "ints": [I; 1, -2], "longs": [L; 1, -2]}),
+        val
+    );
+    let ints: IntArray =
+        serde_json::from_str(r#"{"__fastnbt_int_array": [-2147483648, 2147483647]}"#).unwrap();
+    assert_eq!(IntArray::new(vec![i32::MIN, i32::MAX]), ints);
+
+    let too_big = r#"{"__fastnbt_byte_array": [128]}"#;
+    assert!(serde_json::from_str::<ByteArray>(too_big).is_err());
+}
+
 #[test]
 fn nested() {
     #[derive(Deserialize, PartialEq, Debug)]
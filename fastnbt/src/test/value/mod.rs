@@ -216,3 +216,128 @@ fn distinguish_compound() {
     let v: Value = from_bytes(&input).unwrap();
     assert_contains!(v, "a", Value::Compound(_));
 }
+
+#[test]
+fn pointer() {
+    let v = crate::nbt!({
+        "Inventory": [
+            {"id": "minecraft:stone", "Count": 1i8},
+            {"id": "minecraft:stick", "tag": {"display": {"Name": "Wand"}}},
+        ],
+        "a/b": 1,
+        "m~n": 2,
+        "~1": 3,
+        "": 4,
+        "ints": [I; 1, 2],
+        "lists": [[5]],
+    });
+
+    assert_eq!(Some(&v), v.pointer(""));
+    assert_eq!(
+        Some(&Value::String("Wand".into())),
+        v.pointer("/Inventory/1/tag/display/Name")
+    );
+    assert_eq!(Some(&Value::Byte(1)), v.pointer("/Inventory/0/Count"));
+    assert_eq!(Some(&Value::Int(5)), v.pointer("/lists/0/0"));
+    assert_eq!(Some(&Value::Int(1)), v.pointer("/a~1b"));
+    assert_eq!(Some(&Value::Int(2)), v.pointer("/m~0n"));
+    assert_eq!(Some(&Value::Int(3)), v.pointer("/~01"));
+    assert_eq!(Some(&Value::Int(4)), v.pointer("/"));
+    assert!(matches!(v.pointer("/ints"), Some(Value::IntArray(_))));
+
+    // Out of range and malformed indices.
+    assert_eq!(None, v.pointer("/Inventory/2"));
+    assert_eq!(None, v.pointer("/Inventory/-1"));
+    assert_eq!(None, v.pointer("/Inventory/01"));
+    assert_eq!(None, v.pointer("/Inventory/+1"));
+    assert_eq!(None, v.pointer("/Inventory/id"));
+    assert_eq!(None, v.pointer("/Inventory/99999999999999999999999"));
+
+    // Missing keys, and going inside something that is not a container.
+    assert_eq!(None, v.pointer("Inventory"));
+    assert_eq!(None, v.pointer("/missing"));
+    assert_eq!(None, v.pointer("/a/b"));
+    assert_eq!(None, v.pointer("/ints/0"));
+    assert_eq!(None, v.pointer("/Inventory/0/Count/0"));
+}
+
+#[test]
+fn get_and_pointer_mut() {
+    let mut v = crate::nbt!({
+        "palette": [{"Name": "minecraft:stone"}, {"Name": "minecraft:dirt"}],
+        "size": [3, 2, 1],
+        "ints": [I; 1, 2],
+    });
+
+    assert_eq!(Some(&Value::Int(2)), v.get("size").and_then(|s| s.get(1)));
+    assert_eq!(None, v.get(0));
+    assert_eq!(None, v.get("size").and_then(|s| s.get("0")));
+    assert_eq!(None, v.get("size").and_then(|s| s.get(3)));
+    assert_eq!(None, v.get("ints").and_then(|s| s.get(0)));
+    assert_eq!(None, v.get_mut("missing"));
+    assert_eq!(None, v.get_mut(String::from("size")).unwrap().get_mut("x"));
+
+    // A nested compound field.
+    *v.pointer_mut("/palette/1/Name").unwrap() = "minecraft:grass_block".into();
+    // A list element.
+    if let Some(Value::Int(n)) = v.get_mut("size").and_then(|s| s.get_mut(2)) {
+        *n += 4;
+    }
+    // Adding to a compound found by a pointer.
+    if let Some(Value::Compound(c)) = v.pointer_mut("/palette/0") {
+        c.insert("Properties".into(), crate::nbt!({"axis": "y"}));
+    }
+
+    assert_eq!(
+        crate::nbt!({
+            "palette": [
+                {"Name": "minecraft:stone", "Properties": {"axis": "y"}},
+                {"Name": "minecraft:grass_block"},
+            ],
+            "size": [3, 2, 5],
+            "ints": [I; 1, 2],
+        }),
+        v
+    );
+    let bytes = to_bytes(&v).unwrap();
+    assert_eq!(v, from_bytes::<Value>(&bytes).unwrap());
+
+    assert_eq!(None, v.pointer_mut("/palette/2"));
+    assert_eq!(None, v.pointer_mut("/palette/Name"));
+    assert_eq!(None, v.pointer_mut("/size/0/0"));
+    assert_eq!(None, v.pointer_mut("/ints/0"));
+    assert_eq!(None, v.pointer_mut("size"));
+    assert!(v.pointer_mut("").is_some());
+}
+
+#[cfg(feature = "indexmap")]
+#[test]
+fn ordered_value_keeps_key_order() {
+    use crate::OrderedValue;
+
+    let input = Builder::new()
+        .start_compound("")
+        .int("z", 1)
+        .start_compound("inner")
+        .byte("y", 2)
+        .byte("b", 3)
+        .end_compound()
+        .int_array("a", &[4, 5])
+        .end_compound()
+        .build();
+
+    let v: OrderedValue = from_bytes(&input).unwrap();
+    let OrderedValue::Compound(map) = &v else {
+        panic!("expected compound, got {v:?}")
+    };
+    assert_eq!(vec!["z", "inner", "a"], map.keys().collect::<Vec<_>>());
+    let OrderedValue::Compound(inner) = &map["inner"] else {
+        panic!("expected compound, got {:?}", map["inner"])
+    };
+    assert_eq!(vec!["y", "b"], inner.keys().collect::<Vec<_>>());
+    assert_eq!(input, to_bytes(&v).unwrap());
+
+    let sorted = Value::from(v.clone());
+    assert_eq!(from_bytes::<Value>(&input).unwrap(), sorted);
+    assert_eq!(v, OrderedValue::from(sorted));
+}
@@ -8,9 +8,8 @@ use serde::{
     },
     forward_to_deserialize_any, serde_if_integer128, Deserialize, Deserializer,
 };
-use serde_bytes::ByteBuf;
 
-use crate::{error::Error, ByteArray, IntArray, LongArray, Value};
+use crate::{arrays::ArrayBytes, error::Error, ByteArray, IntArray, LongArray, Value};
 
 impl<'de> Deserialize<'de> for Value {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -25,6 +24,14 @@ impl<'de> Deserialize<'de> for Value {
                 formatter.write_str("valid NBT")
             }
 
+            // NBT has no bool, so like when serializing one it is a byte.
+            fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(Value::Byte(v as i8))
+            }
+
             fn visit_i8<E>(self, v: i8) -> Result<Self::Value, E>
             where
                 E: serde::de::Error,
@@ -110,18 +117,18 @@ impl<'de> Deserialize<'de> for Value {
                         Ok(Value::Compound(compound))
                     }
                     Some(KeyClass::ByteArray) => {
-                        let data = map.next_value::<ByteBuf>()?;
-                        Ok(Value::ByteArray(ByteArray::from_buf(data.into_vec())))
+                        let data = map.next_value::<ArrayBytes<1>>()?;
+                        Ok(Value::ByteArray(ByteArray::from_buf(data.0)))
                     }
                     Some(KeyClass::IntArray) => {
-                        let data = map.next_value::<ByteBuf>()?;
-                        IntArray::from_bytes(&data)
+                        let data = map.next_value::<ArrayBytes<4>>()?;
+                        IntArray::from_bytes(&data.0)
                             .map(Value::IntArray)
                             .map_err(|_| serde::de::Error::custom("could not read int array"))
                     }
                     Some(KeyClass::LongArray) => {
-                        let data = map.next_value::<ByteBuf>()?;
-                        LongArray::from_bytes(&data)
+                        let data = map.next_value::<ArrayBytes<8>>()?;
+                        LongArray::from_bytes(&data.0)
                             .map(Value::LongArray)
                             .map_err(|_| serde::de::Error::custom("could not read long array"))
                     }
@@ -135,9 +142,9 @@ impl<'de> Deserialize<'de> for Value {
     }
 }
 
-struct KeyClassifier;
+pub(super) struct KeyClassifier;
 
-enum KeyClass {
+pub(super) enum KeyClass {
     Compound(String),
     ByteArray,
     IntArray,
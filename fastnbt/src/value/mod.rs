@@ -1,5 +1,7 @@
 mod array_serializer;
 mod de;
+#[cfg(feature = "indexmap")]
+mod ordered;
 mod ser;
 
 use std::collections::BTreeMap;
@@ -8,6 +10,8 @@ use serde::{serde_if_integer128, Deserialize, Serialize};
 
 use crate::{error::Error, ByteArray, IntArray, LongArray};
 
+#[cfg(feature = "indexmap")]
+pub use self::ordered::OrderedValue;
 pub use self::ser::Serializer;
 
 /// Value is a complete NBT value. It owns its data. Compounds and Lists are
@@ -159,6 +163,169 @@ impl Value {
             _ => None,
         }
     }
+
+    /// Look up a value nested inside this one by a JSON Pointer, like
+    /// `serde_json`'s `Value::pointer`. The pointer is a series of tokens that
+    /// each start with `/`, and the empty pointer is this value. A token is a
+    /// key in a compound or an index in a list. As in JSON Pointer, `~1` in a
+    /// key stands for `/` and `~0` for `~`.
+    ///
+    /// Elements of the typed arrays are numbers rather than `Value`s, so they
+    /// cannot be pointed to. Index the array itself instead.
+    ///
+    /// Returns `None` if anything along the way is missing, including an
+    /// index past the end of a list.
+    ///
+    /// ```
+    /// # use fastnbt::{nbt, Value};
+    /// let v = nbt!({
+    ///     "Inventory": [{"id": "minecraft:stick", "tag": {"display": {"Name": "Wand"}}}],
+    /// });
+    /// assert_eq!(
+    ///     Some(&Value::String("Wand".to_owned())),
+    ///     v.pointer("/Inventory/0/tag/display/Name")
+    /// );
+    /// assert_eq!(None, v.pointer("/Inventory/1"));
+    /// ```
+    pub fn pointer(&self, pointer: &str) -> Option<&Value> {
+        pointer_tokens(pointer)?.try_fold(self, |v, token| match v {
+            Value::Compound(c) => c.get(&token),
+            Value::List(l) => parse_index(&token).and_then(|i| l.get(i)),
+            _ => None,
+        })
+    }
+
+    /// Like [`Value::pointer`], but for changing the value in place.
+    ///
+    /// ```
+    /// # use fastnbt::{nbt, Value};
+    /// let mut v = nbt!({"blocks": [{"pos": [0, 0, 0], "state": 3}]});
+    /// *v.pointer_mut("/blocks/0/state").unwrap() = Value::Int(7);
+    /// assert_eq!(nbt!({"blocks": [{"pos": [0, 0, 0], "state": 7}]}), v);
+    /// ```
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut Value> {
+        pointer_tokens(pointer)?.try_fold(self, |v, token| match v {
+            Value::Compound(c) => c.get_mut(&token),
+            Value::List(l) => parse_index(&token).and_then(|i| l.get_mut(i)),
+            _ => None,
+        })
+    }
+
+    /// Get an entry of a compound by its key, or an element of a list by its
+    /// index. Returns `None` if there is no such entry or element, or if the
+    /// index does not suit this value, such as a key into a list.
+    ///
+    /// ```
+    /// # use fastnbt::{nbt, Value};
+    /// let v = nbt!({"Tags": ["a", "b"]});
+    /// assert_eq!(Some(&Value::from("b")), v.get("Tags").and_then(|t| t.get(1)));
+    /// assert_eq!(None, v.get(0));
+    /// ```
+    pub fn get<I: ValueIndex>(&self, index: I) -> Option<&Value> {
+        index.index_into(self)
+    }
+
+    /// Like [`Value::get`], but for changing the value in place.
+    pub fn get_mut<I: ValueIndex>(&mut self, index: I) -> Option<&mut Value> {
+        index.index_into_mut(self)
+    }
+}
+
+mod private {
+    // Only this crate can implement ValueIndex, so that more methods can be
+    // added to it without breaking anything.
+    pub trait Sealed {}
+}
+
+impl private::Sealed for usize {}
+impl private::Sealed for str {}
+impl private::Sealed for String {}
+impl<T: ?Sized + private::Sealed> private::Sealed for &T {}
+
+/// Something a [`Value`] can be indexed by with [`Value::get`] and
+/// [`Value::get_mut`]: a string key for a compound, or a `usize` index for a
+/// list. This is sealed, so cannot be implemented outside of this crate.
+pub trait ValueIndex: private::Sealed {
+    #[doc(hidden)]
+    fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value>;
+
+    #[doc(hidden)]
+    fn index_into_mut<'v>(&self, v: &'v mut Value) -> Option<&'v mut Value>;
+}
+
+impl ValueIndex for usize {
+    fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value> {
+        match v {
+            Value::List(l) => l.get(*self),
+            _ => None,
+        }
+    }
+
+    fn index_into_mut<'v>(&self, v: &'v mut Value) -> Option<&'v mut Value> {
+        match v {
+            Value::List(l) => l.get_mut(*self),
+            _ => None,
+        }
+    }
+}
+
+impl ValueIndex for str {
+    fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value> {
+        match v {
+            Value::Compound(c) => c.get(self),
+            _ => None,
+        }
+    }
+
+    fn index_into_mut<'v>(&self, v: &'v mut Value) -> Option<&'v mut Value> {
+        match v {
+            Value::Compound(c) => c.get_mut(self),
+            _ => None,
+        }
+    }
+}
+
+impl ValueIndex for String {
+    fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value> {
+        self.as_str().index_into(v)
+    }
+
+    fn index_into_mut<'v>(&self, v: &'v mut Value) -> Option<&'v mut Value> {
+        self.as_str().index_into_mut(v)
+    }
+}
+
+impl<T: ?Sized + ValueIndex> ValueIndex for &T {
+    fn index_into<'v>(&self, v: &'v Value) -> Option<&'v Value> {
+        (**self).index_into(v)
+    }
+
+    fn index_into_mut<'v>(&self, v: &'v mut Value) -> Option<&'v mut Value> {
+        (**self).index_into_mut(v)
+    }
+}
+
+/// The unescaped tokens of a JSON Pointer, or `None` if it is neither empty
+/// nor starts with `/`.
+fn pointer_tokens(pointer: &str) -> Option<impl Iterator<Item = String> + '_> {
+    let tokens = match pointer {
+        "" => None,
+        pointer => Some(pointer.strip_prefix('/')?.split('/')),
+    };
+    Some(
+        tokens
+            .into_iter()
+            .flatten()
+            .map(|token| token.replace("~1", "/").replace("~0", "~")),
+    )
+}
+
+/// Parse a JSON Pointer list index, which has no sign or leading zeros.
+fn parse_index(token: &str) -> Option<usize> {
+    if token.starts_with('+') || (token.starts_with('0') && token.len() != 1) {
+        return None;
+    }
+    token.parse().ok()
 }
 
 // ------------- From<T> impls -------------
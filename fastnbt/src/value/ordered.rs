@@ -0,0 +1,223 @@
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use super::de::{KeyClass, KeyClassifier};
+use crate::{arrays::ArrayBytes, ByteArray, IntArray, LongArray, Value};
+
+/// OrderedValue is a [`Value`] that keeps the keys of its compounds in the
+/// order they were inserted or deserialized, where `Value` keeps them sorted.
+/// Serializing it writes them back out in that order, so parsing and then
+/// writing NBT leaves the keys where they were. This requires the `indexmap`
+/// feature.
+///
+/// Convert to and from `Value` with `From`. Converting to a `Value` sorts the
+/// keys.
+///
+/// ```
+/// # use fastnbt::{nbt, OrderedValue, Value};
+/// let v: OrderedValue = nbt!({"z": 1, "a": 2}).into();
+/// let OrderedValue::Compound(mut map) = v else { unreachable!() };
+/// map.insert("m".into(), OrderedValue::Int(3));
+/// assert_eq!(vec!["a", "z", "m"], map.keys().collect::<Vec<_>>());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum OrderedValue {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    String(String),
+    ByteArray(ByteArray),
+    IntArray(IntArray),
+    LongArray(LongArray),
+    List(Vec<OrderedValue>),
+    Compound(IndexMap<String, OrderedValue>),
+}
+
+impl From<Value> for OrderedValue {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Byte(v) => OrderedValue::Byte(v),
+            Value::Short(v) => OrderedValue::Short(v),
+            Value::Int(v) => OrderedValue::Int(v),
+            Value::Long(v) => OrderedValue::Long(v),
+            Value::Float(v) => OrderedValue::Float(v),
+            Value::Double(v) => OrderedValue::Double(v),
+            Value::String(v) => OrderedValue::String(v),
+            Value::ByteArray(v) => OrderedValue::ByteArray(v),
+            Value::IntArray(v) => OrderedValue::IntArray(v),
+            Value::LongArray(v) => OrderedValue::LongArray(v),
+            Value::List(v) => OrderedValue::List(v.into_iter().map(Into::into).collect()),
+            Value::Compound(v) => {
+                OrderedValue::Compound(v.into_iter().map(|(k, v)| (k, v.into())).collect())
+            }
+        }
+    }
+}
+
+impl From<OrderedValue> for Value {
+    fn from(value: OrderedValue) -> Self {
+        match value {
+            OrderedValue::Byte(v) => Value::Byte(v),
+            OrderedValue::Short(v) => Value::Short(v),
+            OrderedValue::Int(v) => Value::Int(v),
+            OrderedValue::Long(v) => Value::Long(v),
+            OrderedValue::Float(v) => Value::Float(v),
+            OrderedValue::Double(v) => Value::Double(v),
+            OrderedValue::String(v) => Value::String(v),
+            OrderedValue::ByteArray(v) => Value::ByteArray(v),
+            OrderedValue::IntArray(v) => Value::IntArray(v),
+            OrderedValue::LongArray(v) => Value::LongArray(v),
+            OrderedValue::List(v) => Value::List(v.into_iter().map(Into::into).collect()),
+            OrderedValue::Compound(v) => {
+                Value::Compound(v.into_iter().map(|(k, v)| (k, v.into())).collect())
+            }
+        }
+    }
+}
+
+impl Serialize for OrderedValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            OrderedValue::Byte(v) => serializer.serialize_i8(*v),
+            OrderedValue::Short(v) => serializer.serialize_i16(*v),
+            OrderedValue::Int(v) => serializer.serialize_i32(*v),
+            OrderedValue::Long(v) => serializer.serialize_i64(*v),
+            OrderedValue::Float(v) => serializer.serialize_f32(*v),
+            OrderedValue::Double(v) => serializer.serialize_f64(*v),
+            OrderedValue::String(v) => serializer.serialize_str(v),
+            OrderedValue::ByteArray(v) => v.serialize(serializer),
+            OrderedValue::IntArray(v) => v.serialize(serializer),
+            OrderedValue::LongArray(v) => v.serialize(serializer),
+            OrderedValue::List(v) => v.serialize(serializer),
+            OrderedValue::Compound(v) => v.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for OrderedValue {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct OrderedValueVisitor;
+        impl<'de> serde::de::Visitor<'de> for OrderedValueVisitor {
+            type Value = OrderedValue;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("valid NBT")
+            }
+
+            fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(OrderedValue::Byte(v as i8))
+            }
+
+            fn visit_i8<E>(self, v: i8) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(OrderedValue::Byte(v))
+            }
+
+            fn visit_i16<E>(self, v: i16) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(OrderedValue::Short(v))
+            }
+
+            fn visit_i32<E>(self, v: i32) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(OrderedValue::Int(v))
+            }
+
+            fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(OrderedValue::Long(v))
+            }
+
+            fn visit_f32<E>(self, v: f32) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(OrderedValue::Float(v))
+            }
+
+            fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(OrderedValue::Double(v))
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(OrderedValue::String(v.to_owned()))
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                let mut v = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                while let Some(el) = seq.next_element()? {
+                    v.push(el);
+                }
+                Ok(OrderedValue::List(v))
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::MapAccess<'de>,
+            {
+                // Arrays are told apart from compounds by their first key, as
+                // for Value.
+                match map.next_key_seed(KeyClassifier)? {
+                    Some(KeyClass::Compound(first_key)) => {
+                        let mut compound = IndexMap::new();
+
+                        compound.insert(first_key, map.next_value()?);
+                        while let Some((key, value)) = map.next_entry()? {
+                            compound.insert(key, value);
+                        }
+
+                        Ok(OrderedValue::Compound(compound))
+                    }
+                    Some(KeyClass::ByteArray) => {
+                        let data = map.next_value::<ArrayBytes<1>>()?;
+                        Ok(OrderedValue::ByteArray(ByteArray::from_buf(data.0)))
+                    }
+                    Some(KeyClass::IntArray) => {
+                        let data = map.next_value::<ArrayBytes<4>>()?;
+                        IntArray::from_bytes(&data.0)
+                            .map(OrderedValue::IntArray)
+                            .map_err(|_| serde::de::Error::custom("could not read int array"))
+                    }
+                    Some(KeyClass::LongArray) => {
+                        let data = map.next_value::<ArrayBytes<8>>()?;
+                        LongArray::from_bytes(&data.0)
+                            .map(OrderedValue::LongArray)
+                            .map_err(|_| serde::de::Error::custom("could not read long array"))
+                    }
+                    None => Ok(OrderedValue::Compound(IndexMap::new())),
+                }
+            }
+        }
+
+        deserializer.deserialize_any(OrderedValueVisitor)
+    }
+}
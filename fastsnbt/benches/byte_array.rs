@@ -0,0 +1,17 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use fastnbt::ByteArray;
+
+pub fn byte_array_benchmark(c: &mut Criterion) {
+    let data: Vec<i8> = (0..1024 * 1024).map(|i| i as i8).collect();
+    let array = ByteArray::new(data);
+
+    c.bench_function("byte_array_1mib", |b| {
+        b.iter(|| {
+            let snbt = fastsnbt::to_vec(&array).unwrap();
+            black_box(snbt);
+        });
+    });
+}
+
+criterion_group!(benches, byte_array_benchmark);
+criterion_main!(benches);
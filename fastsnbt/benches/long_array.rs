@@ -0,0 +1,24 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use fastnbt::LongArray;
+
+pub fn long_array_benchmark(c: &mut Criterion) {
+    let data: Vec<i64> = (0..1024 * 1024).map(|i| i * 0x1234_5678_9abc).collect();
+    let array = LongArray::new(data);
+
+    c.bench_function("long_array_1m", |b| {
+        b.iter(|| {
+            let snbt = fastsnbt::to_vec(&array).unwrap();
+            black_box(snbt);
+        });
+    });
+
+    c.bench_function("long_array_1m_pretty", |b| {
+        b.iter(|| {
+            let snbt = fastsnbt::to_vec_pretty(&array).unwrap();
+            black_box(snbt);
+        });
+    });
+}
+
+criterion_group!(benches, long_array_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,34 @@
+use std::borrow::Cow;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Text<'a> {
+    #[serde(borrow)]
+    text: Cow<'a, str>,
+}
+
+pub fn string_benchmark(c: &mut Criterion) {
+    let plain = "a".repeat(4096);
+    let plain = format!("{{text:\"{plain}\"}}");
+    let escaped = "\\n".to_string() + &"a".repeat(4094);
+    let escaped = format!("{{text:\"{escaped}\"}}");
+
+    c.bench_function("string_4kib", |b| {
+        b.iter(|| {
+            let text: Text = fastsnbt::from_str(&plain).unwrap();
+            black_box(text.text);
+        });
+    });
+
+    c.bench_function("string_4kib_escaped", |b| {
+        b.iter(|| {
+            let text: Text = fastsnbt::from_str(&escaped).unwrap();
+            black_box(text.text);
+        });
+    });
+}
+
+criterion_group!(benches, string_benchmark);
+criterion_main!(benches);
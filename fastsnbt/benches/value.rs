@@ -0,0 +1,34 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use fastnbt::Value;
+use fastsnbt::SerOpts;
+
+const CHUNK_RAW: &[u8] = include_bytes!("../../fastanvil/resources/chunk.nbt");
+
+pub fn value_benchmark(c: &mut Criterion) {
+    let chunk: Value = fastnbt::from_bytes(CHUNK_RAW).unwrap();
+
+    c.bench_function("chunk_to_string", |b| {
+        b.iter(|| {
+            let snbt = fastsnbt::to_string(&chunk).unwrap();
+            black_box(snbt);
+        });
+    });
+
+    c.bench_function("chunk_to_fmt_writer", |b| {
+        b.iter(|| {
+            let mut snbt = String::new();
+            fastsnbt::to_fmt_writer(&mut snbt, &chunk).unwrap();
+            black_box(snbt);
+        });
+    });
+
+    c.bench_function("chunk_value_to_snbt", |b| {
+        b.iter(|| {
+            let snbt = fastsnbt::value_to_snbt(&chunk, SerOpts::new()).unwrap();
+            black_box(snbt);
+        });
+    });
+}
+
+criterion_group!(benches, value_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,32 @@
+use std::io::Write;
+
+use fastsnbt::SerOpts;
+use serde::Serialize;
+
+// This example writes a compound of readings to stdout one field at a time,
+// as they are taken, rather than collecting them into one value to serialize.
+
+#[derive(Serialize)]
+struct Reading {
+    value: f64,
+    unit: &'static str,
+}
+
+fn main() -> fastsnbt::error::Result<()> {
+    let stdout = std::io::stdout().lock();
+    let opts = SerOpts::pretty().unquoted_keys(true).trailing_newline(true);
+    let mut compound = fastsnbt::compound_writer_with_opts(stdout, opts)?;
+
+    compound.field("started", &1_700_000_000_000i64)?;
+    for (i, value) in [20.5, 21.0, 19.75].into_iter().enumerate() {
+        let reading = Reading { value, unit: "C" };
+        compound.field(&format!("reading{i}"), &reading)?;
+        // Let each field reach the terminal as soon as it is written.
+        compound.flush()?;
+    }
+    compound.field("done", &true)?;
+
+    let mut stdout = compound.finish()?;
+    stdout.flush()?;
+    Ok(())
+}
@@ -0,0 +1,140 @@
+//! Conversion between binary NBT and sNBT.
+//!
+//! Binary NBT is often compressed, these functions expect the uncompressed
+//! data, for example from a [`GzDecoder`](https://docs.rs/flate2).
+//!
+//! With the `serde_json` feature there is also conversion between JSON and
+//! sNBT, see `json_to_snbt` and `snbt_to_json`.
+
+use fastnbt::Value;
+
+#[cfg(feature = "serde_json")]
+use crate::error::Error;
+use crate::{error::Result, value_to_snbt, SerOpts};
+
+/// Convert uncompressed binary NBT into a sNBT string.
+///
+/// sNBT has no way to write the name of the root compound, so it is dropped.
+///
+/// ```
+/// let nbt = fastnbt::to_bytes(&fastnbt::nbt!({"a": [B; 1, 2]})).unwrap();
+/// assert_eq!(r#"{"a":[B;1b,2b]}"#, fastsnbt::convert::nbt_to_snbt(&nbt).unwrap());
+/// ```
+pub fn nbt_to_snbt(bytes: &[u8]) -> Result<String> {
+    let value: Value = fastnbt::from_bytes(bytes)?;
+    value_to_snbt(&value, SerOpts::new())
+}
+
+/// Convert a sNBT string into uncompressed binary NBT. The input needs to be
+/// a compound, which becomes the root compound named `root_name`. This is
+/// usually the empty string. Typed arrays like `[I; 1, 2]` become the matching
+/// binary array tags rather than lists.
+///
+/// ```
+/// # use fastnbt::Value;
+/// let nbt = fastsnbt::convert::snbt_to_nbt("{a: [B; 1b, 2b]}", "").unwrap();
+/// let value: Value = fastnbt::from_bytes(&nbt).unwrap();
+/// assert_eq!(fastnbt::nbt!({"a": [B; 1, 2]}), value);
+/// ```
+pub fn snbt_to_nbt(s: &str, root_name: &str) -> Result<Vec<u8>> {
+    let value: Value = crate::from_str(s)?;
+    let opts = fastnbt::SerOpts::new().root_name(root_name);
+    Ok(fastnbt::to_bytes_with_opts(&value, opts)?)
+}
+
+/// Convert JSON into a sNBT string. JSON has no types for numbers, so
+///
+/// - integers become ints, or longs if they do not fit in an int,
+/// - other numbers become doubles, as do integers too large for a long,
+/// - `true` and `false` become the bytes `1b` and `0b`, as in the game,
+/// - arrays become lists, and objects compounds.
+///
+/// `null` has no equivalent and is an error. Objects are written with their
+/// keys sorted.
+///
+/// ```
+/// let json = serde_json::json!({"text": "Hi", "bold": true, "extra": [1, 2.5]});
+/// assert_eq!(
+///     r#"{"bold":1b,"extra":[1,2.5],"text":"Hi"}"#,
+///     fastsnbt::convert::json_to_snbt(&json).unwrap()
+/// );
+/// ```
+#[cfg(feature = "serde_json")]
+pub fn json_to_snbt(v: &serde_json::Value) -> Result<String> {
+    value_to_snbt(&json_to_value(v)?, SerOpts::new())
+}
+
+#[cfg(feature = "serde_json")]
+fn json_to_value(v: &serde_json::Value) -> Result<Value> {
+    use serde_json::Value as Json;
+
+    Ok(match v {
+        Json::Null => {
+            return Err(Error::unsupported_type(
+                "null",
+                "cannot convert JSON null to sNBT".into(),
+            ))
+        }
+        Json::Bool(b) => Value::Byte(*b as i8),
+        Json::Number(n) => match n.as_i64() {
+            Some(n) => match i32::try_from(n) {
+                Ok(n) => Value::Int(n),
+                Err(_) => Value::Long(n),
+            },
+            // Always some without serde_json's arbitrary_precision feature.
+            None => Value::Double(n.as_f64().unwrap_or(f64::NAN)),
+        },
+        Json::String(s) => Value::String(s.clone()),
+        Json::Array(a) => Value::List(a.iter().map(json_to_value).collect::<Result<_>>()?),
+        Json::Object(o) => Value::Compound(
+            o.iter()
+                .map(|(k, v)| Ok((k.clone(), json_to_value(v)?)))
+                .collect::<Result<_>>()?,
+        ),
+    })
+}
+
+/// Convert a sNBT string into JSON. The type of each number is lost, so
+/// converting back with [`json_to_snbt`] may not give the same sNBT. Bytes
+/// stay numbers even if they were written as `true` or `false`, and arrays
+/// become plain JSON arrays. Floats that are not finite, such as `NaNd`, have
+/// no JSON equivalent and are an error.
+///
+/// ```
+/// let json = fastsnbt::convert::snbt_to_json("{a: 1b, b: [I; 2, 3]}").unwrap();
+/// assert_eq!(serde_json::json!({"a": 1, "b": [2, 3]}), json);
+/// ```
+#[cfg(feature = "serde_json")]
+pub fn snbt_to_json(s: &str) -> Result<serde_json::Value> {
+    let value: Value = crate::from_str(s)?;
+    value_to_json(&value)
+}
+
+#[cfg(feature = "serde_json")]
+fn value_to_json(v: &Value) -> Result<serde_json::Value> {
+    use serde_json::Value as Json;
+
+    let float = |f: f64| {
+        serde_json::Number::from_f64(f)
+            .map(Json::Number)
+            .ok_or_else(|| Error::bespoke(format!("cannot convert {f} to JSON")))
+    };
+    Ok(match v {
+        Value::Byte(n) => Json::from(*n),
+        Value::Short(n) => Json::from(*n),
+        Value::Int(n) => Json::from(*n),
+        Value::Long(n) => Json::from(*n),
+        Value::Float(f) => float(*f as f64)?,
+        Value::Double(f) => float(*f)?,
+        Value::String(s) => Json::from(s.as_str()),
+        Value::ByteArray(a) => Json::from(a.to_vec()),
+        Value::IntArray(a) => Json::from(a.to_vec()),
+        Value::LongArray(a) => Json::from(a.to_vec()),
+        Value::List(l) => Json::Array(l.iter().map(value_to_json).collect::<Result<_>>()?),
+        Value::Compound(c) => Json::Object(
+            c.iter()
+                .map(|(k, v)| Ok((k.clone(), value_to_json(v)?)))
+                .collect::<Result<_>>()?,
+        ),
+    })
+}
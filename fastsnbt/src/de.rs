@@ -8,41 +8,190 @@
 //! This deserializer supports [`from_str`](crate::from_str) for
 //! zero-copy deserialization for types like [`&str`] if possible.
 //! If there are escaped characters in the string, it will have
-//! to own the resulting string.
+//! to own the resulting string. A [`Cow<str>`](std::borrow::Cow) field marked
+//! with `#[serde(borrow)]` borrows when it can and owns otherwise.
+//!
+//! ## Top level values
+//! An sNBT document is any single value, not only a compound. `5`, `"text"`,
+//! `[1,2,3]` and `[I;1,2]` are all valid on their own, and
+//! [`to_string`](crate::to_string) writes whatever it is given in the same
+//! way. Whitespace around the value is allowed, anything else after it is an
+//! error. Binary NBT does need a compound at the root, so converting with
+//! [`snbt_to_nbt`](crate::convert::snbt_to_nbt) does too.
+//!
+//! ## Lists
+//! Every element of a list carries its own type: `[1,2,3]` is a list of ints
+//! and `[1b,2b]` a list of bytes. The first element decides the type of the
+//! list when it is written as binary NBT, and `[]` is an empty list of the
+//! end tag. Lists mixing types, like `[1b,2]`, are accepted as they are unless
+//! [`DeOpts::strict_lists`](crate::DeOpts::strict_lists) is set, in which case
+//! the first element that differs is an error.
+//!
+//! ## Byte arrays
+//! A `[B;...]` byte array deserializes into [`fastnbt::ByteArray`], or into
+//! anything taking bytes, like a `Vec<u8>` marked
+//! `#[serde(with = "serde_bytes")]`. The bytes are parsed from text, so they
+//! are always owned rather than borrowed from the input.
+//!
+//! The elements of any typed array may have their own suffix, `b` in a byte
+//! array and `l` in a long array, or no suffix at all. Without a suffix, an
+//! element of a long array is still a long, so `[L; 3000000000]` is fine
+//! though `3000000000` alone is too large for an int. A suffix for another
+//! type, as in `[B; 1b, 2s]`, is an error giving the element's position,
+//! even if the value would fit.
+//!
+//! ## Any value
+//! Deserializing without a type, as [`Value`](fastnbt::Value) or
+//! `serde_json::Value` do, picks the value from the input itself. Lists,
+//! compounds and quoted strings are known from their first character, and
+//! anything else is a number if its suffix makes it one, then a bool, then an
+//! unquoted string. An array is given to the visitor as a map with a single
+//! entry, as described in [the `ser` module](crate::ser#arrays), whose value
+//! is the elements as bytes, or as a sequence of numbers to a visitor that
+//! doesn't ask for bytes. A bool is given as one, which a `Value` stores as
+//! the byte `1b` or `0b`.
+//!
+//! This is also how serde buffers a compound for `#[serde(flatten)]` fields
+//! and internally tagged enums like `#[serde(tag = "id")]`, so both work as
+//! they do for JSON objects, including fields holding NBT arrays.
+//!
+//! ## Compound order
+//! A compound deserialized into a [`Value`](fastnbt::Value) is stored in a
+//! `BTreeMap`, so its keys come back out sorted. Enable the `indexmap` feature
+//! and deserialize into `fastnbt::OrderedValue` instead to
+//! keep them in the order they were written.
+//!
+//! A compound can also be deserialized into any map, such as a `BTreeMap`
+//! whose keys come out sorted or a `HashMap`. Keys can be other types than
+//! strings when written as them, like the integers of `{1:a,2:b}`. An array
+//! deserialized into a map is a single entry, whose key is the array token
+//! and whose value is the elements.
 //!
 //! ## Uuid
 //! Because [`Deserializer`] expects a human-readable format,
-//! `UUID`s are expected to be strings.
+//! `UUID`s are expected to be strings. These may be quoted or not, so
+//! `uuid::Uuid` reads what [`to_string`](crate::to_string) writes as well as
+//! hand written sNBT.
 
-use std::{borrow::Cow, marker::PhantomData};
+use std::{borrow::Cow, collections::HashSet, iter::FusedIterator, marker::PhantomData};
 
 use byteorder::{WriteBytesExt, BE};
+use fastnbt::Tag;
+use nom::IResult;
 use serde::{
     de::{
         self,
-        value::{BorrowedStrDeserializer, BytesDeserializer, SeqAccessDeserializer},
-        DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor,
+        value::{BorrowedStrDeserializer, SeqAccessDeserializer, SeqDeserializer},
+        Deserialize, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor,
     },
     forward_to_deserialize_any,
 };
 
 use crate::{
-    error::Error,
+    error::{Error, Position},
     parser::{
-        parse_bool, parse_f32, parse_f64, parse_i16, parse_i32, parse_i64, parse_i8, parse_str,
+        parse_bedrock_str, parse_bool, parse_bool_no_case, parse_f32, parse_f64, parse_i16,
+        parse_i32, parse_i64, parse_i8, parse_radix_i16, parse_radix_i32, parse_radix_i64,
+        parse_radix_i8, parse_str, parse_unsuffixed_i64, parse_wrapping_i8,
     },
-    BYTE_ARRAY_TOKEN, INT_ARRAY_TOKEN, LONG_ARRAY_TOKEN,
+    DeOpts, Dialect, BYTE_ARRAY_TOKEN, INT_ARRAY_TOKEN, LONG_ARRAY_TOKEN,
 };
 
 pub struct Deserializer<'de> {
     pub(crate) input: &'de str,
     pub(crate) pos: usize,
+    /// The complete input, used to work out line and column of errors.
+    origin: &'de str,
+    pub(crate) opts: DeOpts,
+    /// How many lists and compounds we are currently inside of.
+    depth: usize,
+    /// How many list and array elements have been parsed so far.
+    elements: usize,
+    /// Whether a compound key is being deserialized.
+    in_key: bool,
+    /// The tag of the last scalar parsed, so that an element of a typed array
+    /// can be checked against the array once it is read.
+    scalar_tag: Option<Tag>,
+    /// Statistics about the values parsed so far, if they are being gathered.
+    pub(crate) stats: Option<ParseStats>,
+}
+
+/// Statistics about some sNBT, gathered while parsing it with
+/// [`from_str_with_stats`](crate::from_str_with_stats).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseStats {
+    counts: [usize; 13],
+    max_depth: usize,
+}
+
+impl ParseStats {
+    /// How many values with the given tag were parsed, at any depth. The
+    /// elements of an array are part of it rather than counted on their own,
+    /// and compound keys are not counted.
+    pub fn count(&self, tag: Tag) -> usize {
+        self.counts[tag as usize]
+    }
+
+    /// How many values were parsed in total.
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    /// How deeply lists, arrays and compounds were nested, counted the same
+    /// way as [`DeOpts::max_depth`]. A single scalar has a depth of 0, and
+    /// `{a:[1]}` has a depth of 2.
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
 }
 
 impl<'a, 'de: 'a> Deserializer<'de> {
     #[allow(clippy::should_implement_trait)]
     pub fn from_str(input: &'de str) -> Self {
-        Self { input, pos: 0 }
+        Self::with_opts(input, DeOpts::new())
+    }
+
+    /// Create a deserializer over `input` that uses the given options.
+    pub fn with_opts(input: &'de str, opts: DeOpts) -> Self {
+        Self {
+            input,
+            pos: 0,
+            origin: input,
+            opts,
+            depth: 0,
+            elements: 0,
+            in_key: false,
+            scalar_tag: None,
+            stats: None,
+        }
+    }
+
+    /// The current position of the deserializer in the input.
+    pub fn position(&self) -> Position {
+        Position::new(self.origin, self.pos)
+    }
+
+    /// Skip whitespace before a value at the top level, failing if there is
+    /// nothing else. Empty input would otherwise fail however the type being
+    /// deserialized happens to parse it.
+    pub(crate) fn begin(&mut self) -> Result<(), Error> {
+        self.skip_ws();
+        if self.input.is_empty() {
+            return Err(Error::empty_input().with_position(self.position()));
+        }
+        Ok(())
+    }
+
+    /// Check that only whitespace is left in the input. Call this after
+    /// deserializing a value to reject trailing content, like
+    /// [`from_str`](crate::from_str) does.
+    pub fn end(&mut self) -> Result<(), Error> {
+        self.skip_ws();
+        if self.input.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::input_not_consumed().with_position(self.position()))
+        }
     }
 
     pub(crate) fn advance(&mut self, new_input: &'de str) {
@@ -65,6 +214,204 @@ impl<'a, 'de: 'a> Deserializer<'de> {
         }
     }
 
+    /// Go one level deeper into a list or compound, failing if that is deeper
+    /// than we allow. Without a limit deeply nested input would overflow the
+    /// stack.
+    fn enter_nested(&mut self) -> Result<(), Error> {
+        self.depth += 1;
+        if self.depth > self.opts.max_depth {
+            return Err(Error::too_deep(self.opts.max_depth));
+        }
+        if let Some(stats) = &mut self.stats {
+            stats.max_depth = stats.max_depth.max(self.depth);
+        }
+        Ok(())
+    }
+
+    /// Count another list or array element, failing if that is more than we
+    /// allow.
+    fn count_element(&mut self) -> Result<(), Error> {
+        self.elements += 1;
+        match self.opts.max_elements {
+            Some(max) if self.elements > max => Err(Error::too_many_elements(max)),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn expect_colon(&mut self) -> Result<(), Error> {
+        self.skip_ws();
+        if self.input.chars().next().ok_or(Error::unexpected_eof())? != ':' {
+            return Err(Error::expected_colon());
+        }
+        self.advance(&self.input[':'.len_utf8()..]);
+        self.skip_ws();
+        Ok(())
+    }
+
+    /// Parse a hex or binary integer at the start of the input, if they are
+    /// enabled. One too large for its type is an error.
+    fn radix<T>(
+        &self,
+        parser: fn(&'de str) -> IResult<&'de str, T>,
+        tag: Tag,
+    ) -> Result<Option<(&'de str, T)>, Error> {
+        if self.opts.radix_integers {
+            Ok(in_range(parser(self.input), tag)?.ok())
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Parse a bool at the start of the input, in any case if the options
+    /// allow it. This must be tried before strings, as `true` is also a valid
+    /// unquoted string. Keys like `True` are left as strings.
+    fn bool(&self) -> Option<(&'de str, bool)> {
+        if self.opts.case_insensitive_bools && !self.in_key {
+            parse_bool_no_case(self.input).ok()
+        } else {
+            parse_bool(self.input).ok()
+        }
+    }
+
+    /// Parse a byte at the start of the input. Bedrock also allows unsigned
+    /// bytes, which wrap to negative.
+    fn i8(&self) -> IResult<&'de str, i8> {
+        match self.opts.dialect {
+            Dialect::Java => parse_i8(self.input),
+            Dialect::Bedrock => parse_wrapping_i8(self.input),
+        }
+    }
+
+    /// Parse a string at the start of the input, with the escapes of the
+    /// dialect being read.
+    fn str(&self) -> IResult<&'de str, Cow<'de, str>> {
+        match self.opts.dialect {
+            Dialect::Java => parse_str(self.input),
+            Dialect::Bedrock => parse_bedrock_str(self.input),
+        }
+    }
+
+    /// Parse a string at the start of the input if it starts with a `+`,
+    /// which Bedrock does not allow on numbers.
+    fn plus_str(&self) -> Option<(&'de str, Cow<'de, str>)> {
+        if self.opts.dialect == Dialect::Bedrock && self.input.starts_with('+') {
+            self.str().ok()
+        } else {
+            None
+        }
+    }
+
+    /// The tag of the value at the start of the input, following the same
+    /// precedence as [`deserialize_any`](de::Deserializer::deserialize_any).
+    fn peek_tag(&self) -> Option<Tag> {
+        let input = self.input;
+        let tag = if self.plus_str().is_some() {
+            Tag::String
+        } else if parse_f32(input).is_ok() {
+            Tag::Float
+        } else if parse_f64(input).is_ok() {
+            Tag::Double
+        } else if matches!(self.radix(parse_radix_i8, Tag::Byte), Ok(Some(_))) {
+            Tag::Byte
+        } else if matches!(self.radix(parse_radix_i16, Tag::Short), Ok(Some(_))) {
+            Tag::Short
+        } else if matches!(self.radix(parse_radix_i64, Tag::Long), Ok(Some(_))) {
+            Tag::Long
+        } else if matches!(self.radix(parse_radix_i32, Tag::Int), Ok(Some(_))) {
+            Tag::Int
+        } else if is_int(self.i8()) || self.bool().is_some() {
+            Tag::Byte
+        } else if is_int(parse_i16(input)) {
+            Tag::Short
+        } else if is_int(parse_i64(input)) {
+            Tag::Long
+        } else if is_int(parse_i32(input)) {
+            Tag::Int
+        } else if self.str().is_ok() {
+            Tag::String
+        } else if let Some(rest) = input.strip_prefix('[') {
+            match rest.trim_start().get(..2) {
+                Some("B;") => Tag::ByteArray,
+                Some("I;") => Tag::IntArray,
+                Some("L;") => Tag::LongArray,
+                _ => Tag::List,
+            }
+        } else if input.starts_with('{') {
+            Tag::Compound
+        } else {
+            return None;
+        };
+        Some(tag)
+    }
+
+    /// Count a value with the given tag, if statistics are being gathered.
+    /// Compound keys are not counted.
+    fn record_value(&mut self, tag: Tag) {
+        if let (Some(stats), false) = (&mut self.stats, self.in_key) {
+            stats.counts[tag as usize] += 1;
+        }
+    }
+
+    /// Deserialize a quoted string, number, bool, or unquoted string at the
+    /// start of the input, returning the input after it. Anything other than
+    /// a quoted string is tried as each scalar in turn, and it's important to
+    /// keep these in the correct order -> precedence rules.
+    #[inline(never)]
+    fn deserialize_scalar<V>(&mut self, visitor: V) -> Result<(&'de str, V::Value), Error>
+    where
+        V: Visitor<'de>,
+    {
+        let (input, tag, value): (_, _, Result<V::Value, Error>) =
+            if self.input.starts_with(['"', '\'']) {
+                match self.str() {
+                    Ok((input, Cow::Borrowed(v))) => {
+                        (input, Tag::String, visitor.visit_borrowed_str(v))
+                    }
+                    Ok((input, Cow::Owned(v))) => (input, Tag::String, visitor.visit_string(v)),
+                    Err(e) => return Err(str_error(e)),
+                }
+            } else if let Some((input, v)) = self.plus_str() {
+                let value = match v {
+                    Cow::Borrowed(v) => visitor.visit_borrowed_str(v),
+                    Cow::Owned(v) => visitor.visit_str(&v),
+                };
+                (input, Tag::String, value)
+            } else if let Ok((input, v)) = parse_f32(self.input) {
+                (input, Tag::Float, visitor.visit_f32(v))
+            } else if let Ok((input, v)) = parse_f64(self.input) {
+                (input, Tag::Double, visitor.visit_f64(v))
+            } else if let Some((input, v)) = self.radix(parse_radix_i8, Tag::Byte)? {
+                (input, Tag::Byte, visitor.visit_i8(v))
+            } else if let Some((input, v)) = self.radix(parse_radix_i16, Tag::Short)? {
+                (input, Tag::Short, visitor.visit_i16(v))
+            } else if let Some((input, v)) = self.radix(parse_radix_i64, Tag::Long)? {
+                (input, Tag::Long, visitor.visit_i64(v))
+            } else if let Some((input, v)) = self.radix(parse_radix_i32, Tag::Int)? {
+                (input, Tag::Int, visitor.visit_i32(v))
+            } else if let Ok((input, v)) = in_range(self.i8(), Tag::Byte)? {
+                (input, Tag::Byte, visitor.visit_i8(v))
+            } else if let Ok((input, v)) = in_range(parse_i16(self.input), Tag::Short)? {
+                (input, Tag::Short, visitor.visit_i16(v))
+            } else if let Ok((input, v)) = in_range(parse_i64(self.input), Tag::Long)? {
+                (input, Tag::Long, visitor.visit_i64(v))
+            } else if let Ok((input, v)) = in_range(parse_i32(self.input), Tag::Int)? {
+                (input, Tag::Int, visitor.visit_i32(v))
+            } else if let Some((input, v)) = self.bool() {
+                (input, Tag::Byte, visitor.visit_bool(v))
+            } else if let Ok((input, v)) = self.str() {
+                let value = match v {
+                    Cow::Borrowed(v) => visitor.visit_borrowed_str(v),
+                    Cow::Owned(v) => visitor.visit_str(&v),
+                };
+                (input, Tag::String, value)
+            } else {
+                return Err(Error::invalid_input());
+            };
+        self.record_value(tag);
+        self.scalar_tag = Some(tag);
+        Ok((input, value?))
+    }
+
     pub(crate) fn end_delimiter(&'a mut self, end: &'de str) -> Result<&'de str, Error> {
         self.skip_ws();
         if !self.input.starts_with(end) {
@@ -88,58 +435,51 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
             return Err(Error::unexpected_eof());
         }
 
-        // It's important to keep this in the correct order -> precedence rules
-        let (input, value) = if let Ok((input, v)) = parse_f32(self.input) {
-            visitor.visit_f32(v).map(|v| (input, v))
-        } else if let Ok((input, v)) = parse_f64(self.input) {
-            visitor.visit_f64(v).map(|v| (input, v))
-        } else if let Ok((input, v)) = parse_i8(self.input) {
-            visitor.visit_i8(v).map(|v| (input, v))
-        } else if let Ok((input, v)) = parse_i16(self.input) {
-            visitor.visit_i16(v).map(|v| (input, v))
-        } else if let Ok((input, v)) = parse_i64(self.input) {
-            visitor.visit_i64(v).map(|v| (input, v))
-        } else if let Ok((input, v)) = parse_i32(self.input) {
-            visitor.visit_i32(v).map(|v| (input, v))
-        } else if let Ok((input, v)) = parse_bool(self.input) {
-            visitor.visit_bool(v).map(|v| (input, v))
-        } else if let Ok((input, v)) = parse_str(self.input) {
-            match v {
-                Cow::Borrowed(v) => visitor.visit_borrowed_str(v),
-                Cow::Owned(v) => visitor.visit_str(&v),
-            }
-            .map(|v| (input, v))
-        } else if self.starts_delimiter("[") {
+        // Lists and compounds are known from their first character. Anything
+        // else is a scalar, parsed out of line so that its many parsers do not
+        // add to the stack frame of every level of nesting.
+        let (input, value) = if self.starts_delimiter("[") {
+            self.enter_nested()?;
             self.skip_ws();
-            if self.starts_delimiter("B;") {
+            let result = if self.starts_delimiter("B;") {
+                self.record_value(Tag::ByteArray);
                 match visitor.visit_map(ArrayWrapperAccess::bytes(self)) {
                     Ok(v) => self.end_delimiter("]").map(|input| (input, v)),
                     Err(e) => Err(e),
                 }
             } else if self.starts_delimiter("I;") {
+                self.record_value(Tag::IntArray);
                 match visitor.visit_map(ArrayWrapperAccess::ints(self)) {
                     Ok(v) => self.end_delimiter("]").map(|input| (input, v)),
                     Err(e) => Err(e),
                 }
             } else if self.starts_delimiter("L;") {
+                self.record_value(Tag::LongArray);
                 match visitor.visit_map(ArrayWrapperAccess::longs(self)) {
                     Ok(v) => self.end_delimiter("]").map(|input| (input, v)),
                     Err(e) => Err(e),
                 }
             } else {
-                match visitor.visit_seq(CommaSep::new(self)) {
+                self.record_value(Tag::List);
+                match visitor.visit_seq(CommaSep::list(self)) {
                     Ok(v) => self.end_delimiter("]").map(|input| (input, v)),
                     Err(e) => Err(e),
                 }
-            }
+            };
+            self.depth -= 1;
+            result
         } else if self.starts_delimiter("{") {
+            self.enter_nested()?;
             self.skip_ws();
-            match visitor.visit_map(CommaSep::new(self)) {
+            self.record_value(Tag::Compound);
+            let result = match visitor.visit_map(CommaSep::new(self)) {
                 Ok(v) => self.end_delimiter("}").map(|input| (input, v)),
                 Err(e) => Err(e),
-            }
+            };
+            self.depth -= 1;
+            result
         } else {
-            Err(Error::invalid_input(self.pos))
+            self.deserialize_scalar(visitor)
         }?;
 
         self.advance(input);
@@ -148,9 +488,90 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     }
 
     forward_to_deserialize_any! {
-        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string
-        bytes byte_buf option unit unit_struct newtype_struct seq
-        tuple tuple_struct map struct identifier
+        i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char
+        unit unit_struct newtype_struct seq map struct
+    }
+
+    /// NBT stores bools as bytes, so like `fastnbt` any integer is accepted
+    /// as a bool, as well as `true` and `false`.
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.skip_ws();
+        let int = match self.peek_tag() {
+            _ if self.bool().is_some() => None,
+            Some(Tag::Byte) => in_range(self.i8(), Tag::Byte)?
+                .ok()
+                .map(|(input, v)| (input, v != 0)),
+            Some(Tag::Short) => in_range(parse_i16(self.input), Tag::Short)?
+                .ok()
+                .map(|(input, v)| (input, v != 0)),
+            Some(Tag::Int) => in_range(parse_i32(self.input), Tag::Int)?
+                .ok()
+                .map(|(input, v)| (input, v != 0)),
+            Some(Tag::Long) => in_range(parse_i64(self.input), Tag::Long)?
+                .ok()
+                .map(|(input, v)| (input, v != 0)),
+            _ => None,
+        };
+        let Some((input, v)) = int else {
+            return self.deserialize_any(visitor);
+        };
+        let value = visitor.visit_bool::<Error>(v)?;
+        self.advance(input);
+        self.skip_ws();
+        Ok(value)
+    }
+
+    /// A number or bool is only read as one if it is a whole token, so an
+    /// unquoted string that starts like one, such as the Uuid
+    /// `123e4567-e89b-12d3-a456-426614174000`, is read as a string by
+    /// [`deserialize_any`](de::Deserializer::deserialize_any). Something that
+    /// is a number or bool in its entirety, like `1.5f`, is still not a
+    /// string. Compound keys always are, even ones like `1`.
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.skip_ws();
+        let (input, v) = match self.str() {
+            Ok((input, v)) if self.in_key => (input, v),
+            // Let the visitor say what it found if it is not a string.
+            _ => return self.deserialize_any(visitor),
+        };
+        let value = match v {
+            Cow::Borrowed(v) => visitor.visit_borrowed_str::<Error>(v),
+            Cow::Owned(v) => visitor.visit_string(v),
+        }?;
+        self.advance(input);
+        self.skip_ws();
+        Ok(value)
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    /// Field and variant names are strings, even unquoted ones like `1` that
+    /// would otherwise be read as a number.
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    /// sNBT has no null, so a value that is present is always `Some`. A field
+    /// that is missing from its compound is `None`.
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
     }
 
     fn deserialize_enum<V>(
@@ -162,17 +583,123 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: de::Visitor<'de>,
     {
-        let (input, value) = if let Ok((input, v)) = parse_str(self.input) {
+        self.skip_ws();
+        if self.starts_delimiter("{") {
+            self.enter_nested()?;
+            self.skip_ws();
+            let value = visitor.visit_enum(VariantAccess { de: self })?;
+            self.depth -= 1;
+            let input = self.end_delimiter("}")?;
+            self.advance(input);
+            self.skip_ws();
+            return Ok(value);
+        }
+
+        let (input, value) = if let Ok((input, v)) = self.str() {
             visitor
                 .visit_enum(v.as_ref().into_deserializer())
                 .map(|v| (input, v))
         } else {
-            Err(Error::invalid_input(self.pos))
+            Err(Error::invalid_input())
         }?;
         self.advance(input);
         Ok(value)
     }
 
+    /// Fixed size arrays like `[i32; N]` and tuples are read from lists and
+    /// from typed arrays alike, which is handy for things like heightmaps.
+    /// Unlike other sequences the elements of an array reach the visitor
+    /// directly rather than as the array token map. Too many elements is an
+    /// error, as is too few.
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if !self.starts_delimiter("[") {
+            return self.deserialize_any(visitor);
+        }
+        self.enter_nested()?;
+        self.skip_ws();
+        let array = [("B;", Tag::Byte), ("I;", Tag::Int), ("L;", Tag::Long)]
+            .into_iter()
+            .find(|(prefix, _)| self.starts_delimiter(prefix));
+        let mut seq = match array {
+            Some((_, tag)) => CommaSep::array(self, tag),
+            None => CommaSep::list(self),
+        };
+        let value = visitor.visit_seq(&mut seq)?;
+        if seq.next_element_start()? {
+            let mut found = len + 1;
+            de::IgnoredAny::deserialize(&mut *seq.de)?;
+            while seq.next_element::<de::IgnoredAny>()?.is_some() {
+                found += 1;
+            }
+            return Err(de::Error::invalid_length(
+                found,
+                &format!("{len} elements").as_str(),
+            ));
+        }
+        self.depth -= 1;
+        let input = self.end_delimiter("]")?;
+        self.advance(input);
+        self.skip_ws();
+        Ok(value)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    /// A `B;` byte array is collected in one pass and given to the visitor as
+    /// a byte buffer, rather than as the array token map `deserialize_any`
+    /// gives, which only the fastnbt array types understand. The text has to
+    /// be parsed, so the bytes cannot be borrowed from the input. Anything
+    /// else is deserialized as usual, so lists of bytes reach the visitor as a
+    /// sequence.
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.skip_ws();
+        let is_byte_array = self
+            .input
+            .strip_prefix('[')
+            .is_some_and(|rest| rest.trim_start().starts_with("B;"));
+        if !is_byte_array {
+            return self.deserialize_any(visitor);
+        }
+
+        self.starts_delimiter("[");
+        self.enter_nested()?;
+        self.starts_delimiter("B;");
+        let mut bytes = Vec::new();
+        let mut seq = CommaSep::array(self, Tag::Byte);
+        while let Some(b) = seq.next_element::<i8>()? {
+            bytes.push(b as u8);
+        }
+        self.depth -= 1;
+        let input = self.end_delimiter("]")?;
+        let value = visitor.visit_byte_buf::<Error>(bytes)?;
+        self.advance(input);
+        self.skip_ws();
+        Ok(value)
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_bytes(visitor)
+    }
+
     fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
@@ -181,26 +708,165 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     }
 }
 
+#[cfg(feature = "lossless")]
+impl<'de> Deserializer<'de> {
+    /// Deserialize a [`LosslessValue`](crate::LosslessValue), keeping the
+    /// text of each number that is written differently to how it would be
+    /// serialized. Lists and compounds are walked here so their elements can
+    /// keep theirs too. Arrays are deserialized as usual.
+    pub(crate) fn lossless_value(&mut self) -> Result<crate::LosslessValue, Error> {
+        use crate::LosslessValue;
+        use fastnbt::Value;
+        use std::collections::BTreeMap;
+
+        self.skip_ws();
+        let start = self.input;
+        let is_array = |rest: &str| {
+            ["B;", "I;", "L;"]
+                .iter()
+                .any(|prefix| rest.trim_start().starts_with(prefix))
+        };
+
+        if start.starts_with('[') && !is_array(&start[1..]) {
+            self.starts_delimiter("[");
+            self.enter_nested()?;
+            let mut list = Vec::new();
+            let mut seq = CommaSep::list(self);
+            while seq.next_element_start()? {
+                list.push(seq.de.lossless_value()?);
+            }
+            self.depth -= 1;
+            let input = self.end_delimiter("]")?;
+            self.advance(input);
+            self.skip_ws();
+            return Ok(LosslessValue::List(list));
+        }
+        if self.starts_delimiter("{") {
+            self.enter_nested()?;
+            let mut compound = BTreeMap::new();
+            let mut map = CommaSep::new(self);
+            while let Some(key) = map.next_key::<String>()? {
+                map.de.expect_colon()?;
+                compound.insert(key, map.de.lossless_value()?);
+            }
+            self.depth -= 1;
+            let input = self.end_delimiter("}")?;
+            self.advance(input);
+            self.skip_ws();
+            return Ok(LosslessValue::Compound(compound));
+        }
+
+        let value = Value::deserialize(&mut *self)?;
+        let text = start[..start.len() - self.input.len()].trim_end();
+        let number = matches!(
+            value,
+            Value::Byte(_)
+                | Value::Short(_)
+                | Value::Int(_)
+                | Value::Long(_)
+                | Value::Float(_)
+                | Value::Double(_)
+        );
+        if number && crate::value_to_snbt(&value, crate::SerOpts::new())? != text {
+            return Ok(LosslessValue::Raw {
+                value,
+                text: text.to_owned(),
+            });
+        }
+        Ok(LosslessValue::Value(value))
+    }
+}
+
+/// Whether an integer parser found one, including one too large for its type
+/// which is an error rather than some other kind of token.
+fn is_int<T>(result: IResult<&str, T>) -> bool {
+    !matches!(result, Err(nom::Err::Error(_)))
+}
+
+/// Turn an integer too large for its type into an error naming the type,
+/// instead of letting the caller try to parse it as something else. An int
+/// that would fit a long most likely lacks its `l` suffix, so say so.
+fn in_range<T>(result: IResult<&str, T>, tag: Tag) -> Result<IResult<&str, T>, Error> {
+    match result {
+        Err(nom::Err::Failure(e)) if e.code == nom::error::ErrorKind::TooLarge => {
+            if tag == Tag::Int && e.input.parse::<i64>().is_ok() {
+                return Err(Error::int_out_of_range(e.input));
+            }
+            Err(Error::out_of_range(e.input, tag))
+        }
+        result => Ok(result),
+    }
+}
+
+/// Turn a failure to parse a quoted string into an error, saying so if the
+/// input ran out part way through an escape.
+fn str_error(e: nom::Err<nom::error::Error<&str>>) -> Error {
+    match e {
+        nom::Err::Failure(e) if e.code == nom::error::ErrorKind::Eof => Error::trailing_backslash(),
+        _ => Error::invalid_input(),
+    }
+}
+
+/// Whether an element with the given tag may go in a typed array of
+/// `expected`. An int without a suffix is fine in any array, as long as it
+/// fits, but a suffix must match the array, so `[B; 1, 2s]` is an error.
+/// Anything other than an integer is left for deserializing it to reject,
+/// which gives a better error for something like an out of range number.
+fn array_element_fits(expected: Tag, tag: Tag) -> bool {
+    tag == expected || !matches!(tag, Tag::Byte | Tag::Short | Tag::Long)
+}
+
 struct CommaSep<'a, 'de: 'a> {
     de: &'a mut Deserializer<'de>,
     first: bool,
+    /// Keys seen so far in a compound, if duplicate keys are denied.
+    seen: HashSet<Cow<'de, str>>,
+    /// Whether this is a list, whose elements must share a tag when lists are
+    /// strict.
+    list: bool,
+    /// The tag of the first element, and the index of the next element.
+    tag: Option<Tag>,
+    index: usize,
+    /// The element tag of a typed array, whose elements must fit it.
+    array: Option<Tag>,
 }
 
 impl<'a, 'de> CommaSep<'a, 'de> {
     fn new(de: &'a mut Deserializer<'de>) -> Self {
-        CommaSep { de, first: true }
+        CommaSep {
+            de,
+            first: true,
+            seen: HashSet::new(),
+            list: false,
+            tag: None,
+            index: 0,
+            array: None,
+        }
     }
-}
 
-impl<'a, 'de> SeqAccess<'de> for CommaSep<'a, 'de> {
-    type Error = Error;
+    /// The elements of a typed array, after its `B;`, `I;` or `L;` prefix.
+    fn array(de: &'a mut Deserializer<'de>, tag: Tag) -> Self {
+        CommaSep {
+            array: Some(tag),
+            ..CommaSep::new(de)
+        }
+    }
 
-    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
-    where
-        T: de::DeserializeSeed<'de>,
-    {
+    fn list(de: &'a mut Deserializer<'de>) -> Self {
+        CommaSep {
+            list: true,
+            ..CommaSep::new(de)
+        }
+    }
+}
+
+impl<'a, 'de> CommaSep<'a, 'de> {
+    /// Move to the start of the next list element, past its comma, checking
+    /// its tag if lists are strict. Returns false at the end of the list.
+    fn next_element_start(&mut self) -> Result<bool, Error> {
+        self.de.skip_ws();
         if self.de.input.starts_with(']') {
-            return Ok(None);
+            return Ok(false);
         }
         // Comma is required before every element except the first.
         if !self.first
@@ -214,13 +880,163 @@ impl<'a, 'de> SeqAccess<'de> for CommaSep<'a, 'de> {
         {
             return Err(Error::expected_comma());
         } else if !self.first {
-            self.de.advance(&self.de.input[','.len_utf8()..])
+            self.de.advance(&self.de.input[','.len_utf8()..]);
+            self.de.skip_ws();
+            if self.de.opts.allow_trailing_commas && self.de.input.starts_with(']') {
+                return Ok(false);
+            }
         }
         self.first = false;
-        seed.deserialize(&mut *self.de).map(Some)
+        self.de.count_element()?;
+        if self.list && self.de.opts.strict_lists {
+            if let Some(tag) = self.de.peek_tag() {
+                match self.tag {
+                    Some(expected) if expected != tag => {
+                        return Err(Error::mixed_list(self.index, expected, tag));
+                    }
+                    _ => self.tag = Some(tag),
+                }
+            }
+            self.index += 1;
+        }
+        Ok(true)
     }
 }
 
+impl<'a, 'de> SeqAccess<'de> for CommaSep<'a, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        if !self.next_element_start()? {
+            return Ok(None);
+        }
+        let Some(expected) = self.array else {
+            return seed.deserialize(&mut *self.de).map(Some);
+        };
+        // An element of a long array without a suffix is a long, so may be
+        // too large for the int it would be anywhere else.
+        if expected == Tag::Long && self.de.plus_str().is_none() {
+            if let Ok((input, v)) = parse_unsuffixed_i64(self.de.input) {
+                self.de.advance(input);
+                self.de.skip_ws();
+                self.index += 1;
+                return seed
+                    .deserialize(IntoDeserializer::<Error>::into_deserializer(v))
+                    .map(Some);
+            }
+        }
+        // The element's tag is checked once it has been parsed, which also
+        // takes precedence over the element not fitting what it is read as.
+        let start = self.de.position();
+        self.de.scalar_tag = None;
+        let value = seed.deserialize(&mut *self.de);
+        let index = self.index;
+        self.index += 1;
+        match self.de.scalar_tag {
+            Some(tag) if !array_element_fits(expected, tag) => {
+                Err(Error::mixed_array(index, expected, tag).with_position(start))
+            }
+            _ => value.map(Some),
+        }
+    }
+}
+
+/// An iterator over the elements of a sNBT list, deserializing each one as
+/// it is reached. Create one with [`list_iter`](crate::list_iter).
+///
+/// The iterator ends after the closing `]`, or after the first error. Input
+/// after the list other than whitespace is an error.
+pub struct ListIter<'de, T> {
+    de: Deserializer<'de>,
+    first: bool,
+    tag: Option<Tag>,
+    index: usize,
+    /// An error to yield before anything else, from opening the list.
+    error: Option<Error>,
+    done: bool,
+    marker: PhantomData<T>,
+}
+
+impl<'de, T: Deserialize<'de>> ListIter<'de, T> {
+    pub(crate) fn new(input: &'de str, opts: DeOpts) -> Self {
+        let mut de = Deserializer::with_opts(input, opts);
+        let error = if let Err(e) = de.begin() {
+            Some(e)
+        } else if !de.starts_delimiter("[") {
+            Some(Error::expected_list())
+        } else {
+            de.enter_nested().err()
+        };
+        de.skip_ws();
+        let error = error.or_else(|| {
+            ["B;", "I;", "L;"]
+                .iter()
+                .any(|prefix| de.input.starts_with(prefix))
+                .then(Error::expected_list)
+        });
+        ListIter {
+            error: error.map(|e| e.with_position(de.position())),
+            de,
+            first: true,
+            tag: None,
+            index: 0,
+            done: false,
+            marker: PhantomData,
+        }
+    }
+
+    /// Consume the closing `]` and check nothing but whitespace follows.
+    fn finish(&mut self) -> Result<(), Error> {
+        let input = self.de.end_delimiter("]")?;
+        self.de.advance(input);
+        self.de.skip_ws();
+        if !self.de.input.is_empty() {
+            return Err(Error::input_not_consumed());
+        }
+        Ok(())
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Iterator for ListIter<'de, T> {
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        if let Some(e) = self.error.take() {
+            self.done = true;
+            return Some(Err(e));
+        }
+
+        // Elements are read just as they are for a list in deserialize_any.
+        let mut seq = CommaSep {
+            first: self.first,
+            tag: self.tag,
+            index: self.index,
+            ..CommaSep::list(&mut self.de)
+        };
+        let result = seq.next_element_seed(PhantomData);
+        (self.first, self.tag, self.index) = (seq.first, seq.tag, seq.index);
+
+        let result = match result {
+            Ok(Some(v)) => return Some(Ok(v)),
+            Ok(None) => match self.finish() {
+                Ok(()) => None,
+                Err(e) => Some(Err(e)),
+            },
+            Err(e) => Some(Err(e)),
+        };
+        self.done = true;
+        result.map(|r| r.map_err(|e| e.with_position(self.de.position())))
+    }
+}
+
+impl<'de, T: Deserialize<'de>> FusedIterator for ListIter<'de, T> {}
+
 impl<'a, 'de> MapAccess<'de> for CommaSep<'a, 'de> {
     type Error = Error;
 
@@ -246,33 +1062,89 @@ impl<'a, 'de> MapAccess<'de> for CommaSep<'a, 'de> {
         } else if !self.first {
             self.de.advance(&self.de.input[','.len_utf8()..]);
             self.de.skip_ws();
+            if self.de.opts.allow_trailing_commas && self.de.input.starts_with('}') {
+                return Ok(None);
+            }
         }
         self.first = false;
-        seed.deserialize(&mut *self.de).map(Some)
+        if self.de.opts.deny_duplicate_keys {
+            if let Ok((_, key)) = self.de.str() {
+                if self.seen.contains(&key) {
+                    return Err(Error::duplicate_key(&key));
+                }
+                self.seen.insert(key);
+            }
+        }
+        self.de.in_key = true;
+        let key = seed.deserialize(&mut *self.de).map(Some);
+        self.de.in_key = false;
+        key
     }
 
     fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
     where
         V: de::DeserializeSeed<'de>,
     {
-        self.de.skip_ws();
-        if self
-            .de
-            .input
-            .chars()
-            .next()
-            .ok_or(Error::unexpected_eof())?
-            != ':'
-        {
-            return Err(Error::expected_colon());
-        } else {
-            self.de.advance(&self.de.input[':'.len_utf8()..]);
-            self.de.skip_ws();
-        }
+        self.de.expect_colon()?;
         seed.deserialize(&mut *self.de)
     }
 }
 
+/// Access to an externally tagged enum variant with data, ie a compound with
+/// the variant name as its only key.
+struct VariantAccess<'a, 'de: 'a> {
+    de: &'a mut Deserializer<'de>,
+}
+
+impl<'a, 'de> de::EnumAccess<'de> for VariantAccess<'a, 'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(&mut *self.de)?;
+        self.de.expect_colon()?;
+        Ok((variant, self))
+    }
+}
+
+impl<'a, 'de> de::VariantAccess<'de> for VariantAccess<'a, 'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Err(Error::bespoke(
+            "expected unit variant as a string, found compound".to_string(),
+        ))
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        seed.deserialize(&mut *self.de)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        de::Deserializer::deserialize_seq(&mut *self.de, visitor)
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        de::Deserializer::deserialize_map(&mut *self.de, visitor)
+    }
+}
+
 struct ArrayWrapperAccess<'a, 'de: 'a> {
     de: &'a mut Deserializer<'de>,
     token: &'static str,
@@ -303,6 +1175,44 @@ impl<'a, 'de> ArrayWrapperAccess<'a, 'de> {
             read: false,
         }
     }
+
+    /// Deserialize the elements of the array, as the value of the token.
+    fn elements<V>(&mut self, seed: V) -> Result<V::Value, Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        match self.token {
+            BYTE_ARRAY_TOKEN => {
+                let data = <Vec<i8> as de::Deserialize>::deserialize(SeqAccessDeserializer::new(
+                    CommaSep::array(self.de, Tag::Byte),
+                ))?;
+                let data = unsafe { &*(data.as_slice() as *const [i8] as *const [u8]) };
+                seed.deserialize(ArrayData {
+                    bytes: data,
+                    tag: Tag::Byte,
+                })
+            }
+            INT_ARRAY_TOKEN => {
+                let data = NumStride::<i32>(PhantomData).deserialize(
+                    SeqAccessDeserializer::new(CommaSep::array(self.de, Tag::Int)),
+                )?;
+                seed.deserialize(ArrayData {
+                    bytes: &data.bytes,
+                    tag: Tag::Int,
+                })
+            }
+            LONG_ARRAY_TOKEN => {
+                let data = NumStride::<i64>(PhantomData).deserialize(
+                    SeqAccessDeserializer::new(CommaSep::array(self.de, Tag::Long)),
+                )?;
+                seed.deserialize(ArrayData {
+                    bytes: &data.bytes,
+                    tag: Tag::Long,
+                })
+            }
+            _ => unreachable!("Cannot have a different NBT array type"),
+        }
+    }
 }
 
 impl<'a, 'de> MapAccess<'de> for ArrayWrapperAccess<'a, 'de> {
@@ -325,27 +1235,67 @@ impl<'a, 'de> MapAccess<'de> for ArrayWrapperAccess<'a, 'de> {
     where
         V: de::DeserializeSeed<'de>,
     {
-        match self.token {
-            BYTE_ARRAY_TOKEN => {
-                let data = <Vec<i8> as de::Deserialize>::deserialize(SeqAccessDeserializer::new(
-                    CommaSep::new(self.de),
-                ))?;
-                let data = unsafe { &*(data.as_slice() as *const [i8] as *const [u8]) };
-                seed.deserialize(BytesDeserializer::new(data))
-            }
-            INT_ARRAY_TOKEN => {
-                let data = NumStride::<i32>(PhantomData)
-                    .deserialize(SeqAccessDeserializer::new(CommaSep::new(self.de)))?;
-                seed.deserialize(BytesDeserializer::new(&data.bytes))
-            }
-            LONG_ARRAY_TOKEN => {
-                let data = NumStride::<i64>(PhantomData)
-                    .deserialize(SeqAccessDeserializer::new(CommaSep::new(self.de)))?;
-                seed.deserialize(BytesDeserializer::new(&data.bytes))
+        // The elements are part of the array, so are not counted as values.
+        let stats = self.de.stats.take();
+        let result = self.elements(seed);
+        self.de.stats = stats;
+        result
+    }
+}
+
+/// The elements of an NBT array, as the big-endian bytes the array types in
+/// `fastnbt` expect. Something deserializing without a type, like
+/// `serde_json::Value`, gets the elements as a sequence of numbers instead.
+struct ArrayData<'a> {
+    bytes: &'a [u8],
+    tag: Tag,
+}
+
+impl<'de, 'a> de::Deserializer<'de> for ArrayData<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.tag {
+            Tag::Byte => {
+                SeqDeserializer::new(self.bytes.iter().map(|&b| b as i8)).deserialize_any(visitor)
             }
-            _ => unreachable!("Cannot have a different NBT array type"),
+            Tag::Int => SeqDeserializer::new(
+                self.bytes
+                    .chunks_exact(4)
+                    .map(|b| i32::from_be_bytes(b.try_into().unwrap())),
+            )
+            .deserialize_any(visitor),
+            _ => SeqDeserializer::new(
+                self.bytes
+                    .chunks_exact(8)
+                    .map(|b| i64::from_be_bytes(b.try_into().unwrap())),
+            )
+            .deserialize_any(visitor),
         }
     }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_bytes(self.bytes)
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_bytes(self.bytes)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
 }
 
 struct NumStride<T>(PhantomData<T>);
@@ -1,24 +1,127 @@
 //! Contains the [`Error`] and [`Result`] type used by the deserializer.
-use std::fmt::Display;
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+};
+use core::fmt::{self, Display};
+#[cfg(feature = "std")]
+use std::io;
+
+#[cfg(feature = "std")]
+use fastnbt::Tag;
 
 /// Various errors that can occur during (de)serialization.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Error(String);
+pub struct Error(Box<ErrorImpl>);
+
+/// The contents of an [`Error`], boxed so that a `Result` holding one stays
+/// small. Each level of nesting in the deserializer has some on its stack.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ErrorImpl {
+    kind: ErrorKind,
+    msg: String,
+    position: Option<Position>,
+}
+
+/// What kind of error an [`Error`] is, for handling errors without matching
+/// on their messages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The input is not valid sNBT, or has more after the value. Where is
+    /// given by [`Error::position`].
+    Syntax,
+    /// The input ended part way through a value.
+    Eof,
+    /// An integer does not fit the type given by its suffix.
+    #[cfg(feature = "std")]
+    OutOfRange { tag: Tag },
+    /// The input nests deeper than [`DeOpts::max_depth`](crate::DeOpts::max_depth).
+    TooDeep { max_depth: usize },
+    /// The input has more list and array elements than
+    /// [`DeOpts::max_elements`](crate::DeOpts::max_elements).
+    TooManyElements { max_elements: usize },
+    /// A compound repeats a key, and duplicate keys are denied.
+    DuplicateKey { key: String },
+    /// A list element has a different tag to the first element, and lists
+    /// are strict.
+    #[cfg(feature = "std")]
+    MixedList {
+        index: usize,
+        expected: Tag,
+        found: Tag,
+    },
+    /// An element of a typed array has a suffix for a different type, like
+    /// the short in `[B; 1b, 2s]`.
+    #[cfg(feature = "std")]
+    MixedArray {
+        index: usize,
+        expected: Tag,
+        found: Tag,
+    },
+    /// A value of a type sNBT cannot represent where it appeared, such as
+    /// `()`, or a sequence as a compound key.
+    UnsupportedType { what: &'static str },
+    /// Something other than integers was serialized as an NBT array.
+    ExpectedArray,
+    /// Serializing wrote more than
+    /// [`SerOpts::max_output_bytes`](crate::SerOpts::max_output_bytes).
+    OutputTooLarge { limit: usize },
+    /// Reading or writing failed.
+    #[cfg(feature = "std")]
+    Io(io::ErrorKind),
+    /// Writing to a [`fmt::Write`] failed.
+    Fmt,
+    /// Converting to or from NBT failed.
+    Nbt,
+    /// Any other error, including those raised by `Serialize` and
+    /// `Deserialize` implementations.
+    Custom,
+}
+
+/// Where in the input a deserialization error occurred.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    /// Offset in bytes from the start of the input.
+    pub offset: usize,
+    /// Line number, starting at 1.
+    pub line: usize,
+    /// Column in characters, starting at 1.
+    pub column: usize,
+}
+
+#[cfg(feature = "std")]
+impl Position {
+    pub(crate) fn new(input: &str, offset: usize) -> Position {
+        let before = &input[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Position {
+            offset,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
 
 /// Convenience type for Result.
-pub type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = core::result::Result<T, Error>;
 
-impl std::error::Error for Error {}
+impl core::error::Error for Error {}
 
-impl std::fmt::Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(&self.0)
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0.msg)?;
+        if let Some(pos) = self.0.position {
+            write!(f, " at line {} column {}", pos.line, pos.column)?;
+        }
+        Ok(())
     }
 }
 
 impl serde::de::Error for Error {
     fn custom<T: Display>(msg: T) -> Self {
-        Error(msg.to_string())
+        Error::new(ErrorKind::Custom, msg.to_string())
     }
 }
 
@@ -27,46 +130,187 @@ impl serde::ser::Error for Error {
     where
         T: Display,
     {
-        Error(msg.to_string())
+        Error::new(ErrorKind::Custom, msg.to_string())
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<fastnbt::error::Error> for Error {
+    fn from(e: fastnbt::error::Error) -> Self {
+        Error::new(ErrorKind::Nbt, format!("nbt error: {}", e))
     }
 }
 
-impl From<std::io::Error> for Error {
-    fn from(e: std::io::Error) -> Self {
-        Error(format!("io error: {}", e))
+#[cfg(feature = "std")]
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::new(ErrorKind::Io(e.kind()), format!("io error: {}", e))
     }
 }
 
 impl Error {
-    pub(crate) fn invalid_input(pos: usize) -> Error {
-        Error(format!("invalid input at {}", pos))
+    fn new(kind: ErrorKind, msg: String) -> Error {
+        Error(Box::new(ErrorImpl {
+            kind,
+            msg,
+            position: None,
+        }))
+    }
+
+    /// What kind of error this is.
+    pub fn kind(&self) -> &ErrorKind {
+        &self.0.kind
+    }
+
+    /// Where in the input the error occurred, if known. This is known for
+    /// errors returned when deserializing with [`from_str`](crate::from_str).
+    pub fn position(&self) -> Option<Position> {
+        self.0.position
+    }
+
+    pub(crate) fn output_too_large(max_output_bytes: usize) -> Error {
+        Error::new(
+            ErrorKind::OutputTooLarge {
+                limit: max_output_bytes,
+            },
+            format!("output exceeded {max_output_bytes} bytes"),
+        )
+    }
+
+    pub(crate) fn array_as_other() -> Error {
+        Error::new(
+            ErrorKind::ExpectedArray,
+            "expected NBT Array: use ByteArray, IntArray or LongArray types".into(),
+        )
+    }
+
+    pub(crate) fn unsupported_type(what: &'static str, msg: String) -> Error {
+        Error::new(ErrorKind::UnsupportedType { what }, msg)
+    }
+
+    pub(crate) fn bespoke(msg: String) -> Error {
+        Error::new(ErrorKind::Custom, msg)
+    }
+
+    pub(crate) fn formatter_failed() -> Error {
+        Error::new(ErrorKind::Fmt, format!("fmt error: {}", fmt::Error))
+    }
+}
+
+/// Errors only the deserializer raises.
+#[cfg(feature = "std")]
+impl Error {
+    /// Record where the error occurred, unless it is already known.
+    pub(crate) fn with_position(mut self, position: Position) -> Error {
+        self.0.position.get_or_insert(position);
+        self
+    }
+
+    pub(crate) fn invalid_input() -> Error {
+        Error::new(ErrorKind::Syntax, "invalid input".into())
     }
 
     pub(crate) fn input_not_consumed() -> Error {
-        Error("Input wasn't fully consumed".into())
+        Error::new(ErrorKind::Syntax, "Input wasn't fully consumed".into())
     }
 
     pub(crate) fn expected_comma() -> Error {
-        Error("expected comma".into())
+        Error::new(ErrorKind::Syntax, "expected comma".into())
     }
 
     pub(crate) fn expected_colon() -> Error {
-        Error("expected colon".into())
+        Error::new(ErrorKind::Syntax, "expected colon".into())
     }
 
     pub(crate) fn expected_collection_end() -> Error {
-        Error("expected ] or } end".into())
+        Error::new(ErrorKind::Syntax, "expected ] or } end".into())
+    }
+
+    pub(crate) fn expected_list() -> Error {
+        Error::new(ErrorKind::Syntax, "expected a list".into())
+    }
+
+    pub(crate) fn invalid_utf8(e: core::str::Utf8Error) -> Error {
+        Error::new(ErrorKind::Syntax, format!("input is not valid UTF-8: {e}"))
     }
 
     pub(crate) fn unexpected_eof() -> Error {
-        Error("eof: unexpectedly ran out of input".to_owned())
+        Error::new(
+            ErrorKind::Eof,
+            "eof: unexpectedly ran out of input".to_owned(),
+        )
     }
 
-    pub(crate) fn array_as_other() -> Error {
-        Error("expected NBT Array: use ByteArray, IntArray or LongArray types".into())
+    pub(crate) fn empty_input() -> Error {
+        Error::new(
+            ErrorKind::Eof,
+            "unexpected end of input: empty sNBT, expected a value".into(),
+        )
     }
 
-    pub(crate) fn bespoke(msg: String) -> Error {
-        Error(msg)
+    pub(crate) fn trailing_backslash() -> Error {
+        Error::new(
+            ErrorKind::Eof,
+            "trailing backslash / incomplete escape at end of string".into(),
+        )
+    }
+
+    pub(crate) fn duplicate_key(key: &str) -> Error {
+        Error::new(
+            ErrorKind::DuplicateKey { key: key.into() },
+            format!("duplicate key in compound: {key}"),
+        )
+    }
+
+    pub(crate) fn mixed_list(index: usize, expected: Tag, found: Tag) -> Error {
+        Error::new(
+            ErrorKind::MixedList {
+                index,
+                expected,
+                found,
+            },
+            format!(
+                "list element at index {index} is {found}, expected {expected} like the first element"
+            ),
+        )
+    }
+
+    pub(crate) fn mixed_array(index: usize, expected: Tag, found: Tag) -> Error {
+        Error::new(
+            ErrorKind::MixedArray {
+                index,
+                expected,
+                found,
+            },
+            format!("array element at index {index} is {found}, expected {expected}"),
+        )
+    }
+
+    pub(crate) fn too_deep(max_depth: usize) -> Error {
+        Error::new(
+            ErrorKind::TooDeep { max_depth },
+            format!("exceeded max nesting depth of {max_depth}"),
+        )
+    }
+
+    pub(crate) fn too_many_elements(max_elements: usize) -> Error {
+        Error::new(
+            ErrorKind::TooManyElements { max_elements },
+            format!("exceeded max of {max_elements} list and array elements"),
+        )
+    }
+
+    pub(crate) fn out_of_range(value: &str, tag: Tag) -> Error {
+        Error::new(
+            ErrorKind::OutOfRange { tag },
+            format!("value {value} out of range for {tag} (TAG_{tag:?})"),
+        )
+    }
+
+    pub(crate) fn int_out_of_range(value: &str) -> Error {
+        Error::new(
+            ErrorKind::OutOfRange { tag: Tag::Int },
+            format!("int literal {value} out of range, did you mean to add 'l'?"),
+        )
     }
 }
@@ -0,0 +1,141 @@
+//! Helpers for use with `#[serde(with = "...")]`, for types that Minecraft
+//! stores in a particular way.
+
+/// (De)serialize a [`Uuid`](uuid::Uuid) as an int array of four ints, most
+/// significant first, as newer versions of Minecraft store them. Requires the
+/// `uuid` feature.
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use uuid::Uuid;
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// struct Entity {
+///     #[serde(rename = "UUID", with = "fastsnbt::helpers::uuid_int_array")]
+///     uuid: Uuid,
+/// }
+///
+/// let entity = Entity { uuid: Uuid::from_u128(1 << 96 | 2) };
+/// let snbt = fastsnbt::to_string(&entity).unwrap();
+/// assert_eq!(r#"{"UUID":[I;1,0,0,2]}"#, snbt);
+/// assert_eq!(entity, fastsnbt::from_str(&snbt).unwrap());
+/// ```
+#[cfg(feature = "uuid")]
+pub mod uuid_int_array {
+    use fastnbt::IntArray;
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+    use uuid::Uuid;
+
+    pub fn serialize<S: Serializer>(uuid: &Uuid, serializer: S) -> Result<S::Ok, S::Error> {
+        let n = uuid.as_u128();
+        let ints = (0..4)
+            .rev()
+            .map(|i| (n >> (i * 32)) as u32 as i32)
+            .collect();
+        IntArray::new(ints).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Uuid, D::Error> {
+        let array = IntArray::deserialize(deserializer)?;
+        if array.len() != 4 {
+            return Err(de::Error::invalid_length(
+                array.len(),
+                &"an int array of 4 ints",
+            ));
+        }
+        let n = array.iter().fold(0u128, |n, &i| n << 32 | i as u32 as u128);
+        Ok(Uuid::from_u128(n))
+    }
+}
+
+/// (De)serialize a [`SystemTime`](std::time::SystemTime) as a long of
+/// milliseconds since the Unix epoch. Anything finer than a millisecond is
+/// truncated towards the start of time.
+///
+/// ```
+/// use std::time::{Duration, SystemTime};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// struct Save {
+///     #[serde(with = "fastsnbt::helpers::epoch_millis")]
+///     last_played: SystemTime,
+/// }
+///
+/// let save = Save { last_played: SystemTime::UNIX_EPOCH + Duration::from_millis(1500) };
+/// let snbt = fastsnbt::to_string(&save).unwrap();
+/// assert_eq!(r#"{"last_played":1500l}"#, snbt);
+/// assert_eq!(save, fastsnbt::from_str(&snbt).unwrap());
+/// ```
+pub mod epoch_millis {
+    use std::time::{Duration, SystemTime};
+
+    use serde::{de, ser, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+        let millis = match time.duration_since(SystemTime::UNIX_EPOCH) {
+            Ok(after) => i64::try_from(after.as_millis()).ok(),
+            Err(before) => {
+                let before = before.duration();
+                // Round away from the epoch so that the time is truncated
+                // towards the start of time on both sides of it.
+                let millis =
+                    before.as_millis() + u128::from(before.subsec_nanos() % 1_000_000 != 0);
+                i64::try_from(millis).ok().map(|m| -m)
+            }
+        };
+        let millis =
+            millis.ok_or_else(|| ser::Error::custom("time out of range for epoch millis"))?;
+        serializer.serialize_i64(millis)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SystemTime, D::Error> {
+        let millis = i64::deserialize(deserializer)?;
+        let offset = Duration::from_millis(millis.unsigned_abs());
+        let time = if millis >= 0 {
+            SystemTime::UNIX_EPOCH.checked_add(offset)
+        } else {
+            SystemTime::UNIX_EPOCH.checked_sub(offset)
+        };
+        time.ok_or_else(|| de::Error::custom(format!("epoch millis {millis} out of range")))
+    }
+}
+
+/// (De)serialize a chrono [`DateTime<Utc>`](chrono::DateTime) as a long of
+/// milliseconds since the Unix epoch. Requires the `chrono` feature.
+///
+/// ```
+/// use chrono::{DateTime, Utc};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// struct Save {
+///     #[serde(with = "fastsnbt::helpers::chrono_epoch_millis")]
+///     last_played: DateTime<Utc>,
+/// }
+///
+/// let save = Save { last_played: DateTime::from_timestamp_millis(1500).unwrap() };
+/// let snbt = fastsnbt::to_string(&save).unwrap();
+/// assert_eq!(r#"{"last_played":1500l}"#, snbt);
+/// assert_eq!(save, fastsnbt::from_str(&snbt).unwrap());
+/// ```
+#[cfg(feature = "chrono")]
+pub mod chrono_epoch_millis {
+    use chrono::{DateTime, Utc};
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        time: &DateTime<Utc>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(time.timestamp_millis())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<DateTime<Utc>, D::Error> {
+        let millis = i64::deserialize(deserializer)?;
+        DateTime::from_timestamp_millis(millis)
+            .ok_or_else(|| de::Error::custom(format!("epoch millis {millis} out of range")))
+    }
+}
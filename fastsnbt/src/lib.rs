@@ -8,7 +8,8 @@
 //!   NBT related things.
 //!
 //! # Example
-//! ```
+#![cfg_attr(feature = "std", doc = "```")]
+#![cfg_attr(not(feature = "std"), doc = "```ignore")]
 //! # use serde::{Serialize, Deserialize};
 //! #[derive(Debug, PartialEq, Serialize, Deserialize)]
 //! struct SimpleStruct {
@@ -27,81 +28,993 @@
 //! let de: SimpleStruct = fastsnbt::from_str(input).unwrap();
 //! assert_eq!(data, de);
 //! ```
+//!
+//! # Debugging
+//! [`Snbt`] wraps anything serializable to display it as sNBT, which is handy
+//! for logging. The alternate flag, `{:#}`, pretty prints it.
+#![cfg_attr(feature = "std", doc = "```")]
+#![cfg_attr(not(feature = "std"), doc = "```ignore")]
+//! use fastsnbt::Snbt;
+//!
+//! let value = fastnbt::nbt!({"pos": [1, 2]});
+//! println!("{:#}", Snbt(&value));
+//! assert_eq!("{\"pos\":[1,2]}", Snbt(&value).to_string());
+//! assert_eq!("{\n    \"pos\": [\n        1,\n        2\n    ]\n}", format!("{:#}", Snbt(&value)));
+//! ```
+//!
+//! # `no_std`
+//! The `std` feature is on by default. Without it the crate is `no_std`, and
+//! needs only `alloc`. What is left is the serializer: the `to_*` functions
+//! other than [`value_to_snbt`], [`Snbt`], and [`ser::Serializer`] writing into
+//! anything that implements [`ser::Write`]. The deserializer and everything to
+//! do with [`Value`] need `fastnbt`, which needs `std`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
 
-use de::Deserializer;
-use error::Result;
-use ser::Serializer;
+extern crate alloc;
+
+#[cfg(feature = "std")]
+use alloc::borrow::Cow;
+use alloc::{format, string::String, vec::Vec};
+use core::fmt;
+#[cfg(feature = "std")]
+use std::io::Read;
+
+#[cfg(feature = "std")]
+use de::{Deserializer, ListIter, ParseStats};
+#[cfg(feature = "std")]
+use error::Position;
+use error::{Error, Result};
+#[cfg(feature = "std")]
+use fastnbt::Value;
+use ser::{Serializer, Write};
 use serde::Serialize;
+#[cfg(feature = "std")]
+use serde::{de::DeserializeOwned, Deserialize};
 
+#[cfg(feature = "std")]
+pub mod convert;
+#[cfg(feature = "std")]
 pub mod de;
 pub mod error;
+#[cfg(feature = "std")]
+pub mod helpers;
+#[cfg(feature = "lossless")]
+mod lossless;
+// The serializer only needs `is_simple_string`, the rest is for the
+// deserializer.
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
 pub(crate) mod parser;
 pub mod ser;
+#[cfg(feature = "std")]
+mod value_ext;
+
+#[cfg(feature = "lossless")]
+pub use lossless::LosslessValue;
+#[cfg(feature = "std")]
+pub use value_ext::ValueExt;
+
+/// Key of a single entry map that serializes to a byte array, `[B;...]`. The
+/// value must be serialized as bytes. See [the `ser` module](ser#arrays).
+pub const BYTE_ARRAY_TOKEN: &str = "__fastnbt_byte_array";
+
+/// Key of a single entry map that serializes to an int array, `[I;...]`. The
+/// value must be serialized as big-endian bytes, four per int. See
+/// [the `ser` module](ser#arrays).
+pub const INT_ARRAY_TOKEN: &str = "__fastnbt_int_array";
 
-pub(crate) const BYTE_ARRAY_TOKEN_STR: &str = "\"__fastnbt_byte_array\"";
-pub(crate) const INT_ARRAY_TOKEN_STR: &str = "\"__fastnbt_int_array\"";
-pub(crate) const LONG_ARRAY_TOKEN_STR: &str = "\"__fastnbt_long_array\"";
-pub(crate) const BYTE_ARRAY_TOKEN: &str = "__fastnbt_byte_array";
-pub(crate) const INT_ARRAY_TOKEN: &str = "__fastnbt_int_array";
-pub(crate) const LONG_ARRAY_TOKEN: &str = "__fastnbt_long_array";
+/// Key of a single entry map that serializes to a long array, `[L;...]`. The
+/// value must be serialized as big-endian bytes, eight per long. See
+/// [the `ser` module](ser#arrays).
+pub const LONG_ARRAY_TOKEN: &str = "__fastnbt_long_array";
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests;
 
 /// Deserialize into a `T` from some sNBT data. See the
 /// [`de`] module for more information.
+///
+/// [`Value`] is defined in `fastnbt`, which does not depend
+/// on this crate, so it cannot implement `FromStr` by parsing sNBT, and
+/// `"{a:1}".parse::<Value>()` does not work. Use this function or
+/// [`ValueExt::from_snbt`] instead:
+///
+/// ```
+/// use fastnbt::{nbt, Value};
+///
+/// let v: Value = fastsnbt::from_str("{a:1}").unwrap();
+/// assert_eq!(nbt!({"a": 1}), v);
+/// ```
+#[cfg(feature = "std")]
 pub fn from_str<'a, T>(input: &'a str) -> Result<T>
 where
     T: serde::de::Deserialize<'a>,
 {
+    from_str_with_opts(input, DeOpts::new())
+}
+
+/// Deserialize into a `T` from some sNBT data. See the [`de`] module for more
+/// information. The options allow you to make the deserializer stricter than
+/// the game is.
+///
+/// ```
+/// # use fastnbt::Value;
+/// # use fastsnbt::DeOpts;
+/// let opts = DeOpts::new().deny_duplicate_keys(true);
+/// assert!(fastsnbt::from_str_with_opts::<Value>("{a:1,a:2}", opts).is_err());
+/// ```
+#[cfg(feature = "std")]
+pub fn from_str_with_opts<'a, T>(input: &'a str, opts: DeOpts) -> Result<T>
+where
+    T: serde::de::Deserialize<'a>,
+{
+    let mut des = Deserializer::with_opts(input, opts);
+    des.begin()?;
+    let t = T::deserialize(&mut des).map_err(|e| e.with_position(des.position()))?;
+    des.end()?;
+    Ok(t)
+}
+
+/// Deserialize a `T` from the start of some sNBT data, returning it along with
+/// the rest of the input after it. Unlike [`from_str`] anything may follow the
+/// value, which suits reading several values out of one string.
+///
+/// ```
+/// let (first, rest): (i32, _) = fastsnbt::from_str_prefix("1 2 3").unwrap();
+/// assert_eq!((1, "2 3"), (first, rest));
+///
+/// assert!(fastsnbt::from_str::<i32>("1 2 3").is_err());
+/// ```
+#[cfg(feature = "std")]
+pub fn from_str_prefix<'a, T>(input: &'a str) -> Result<(T, &'a str)>
+where
+    T: serde::de::Deserialize<'a>,
+{
+    from_str_prefix_with_opts(input, DeOpts::new())
+}
+
+/// Deserialize a `T` from the start of some sNBT data, with the given options.
+/// See [`from_str_prefix`].
+#[cfg(feature = "std")]
+pub fn from_str_prefix_with_opts<'a, T>(input: &'a str, opts: DeOpts) -> Result<(T, &'a str)>
+where
+    T: serde::de::Deserialize<'a>,
+{
+    let mut des = Deserializer::with_opts(input, opts);
+    des.begin()?;
+    let t = T::deserialize(&mut des).map_err(|e| e.with_position(des.position()))?;
+    des.skip_ws();
+    Ok((t, des.input))
+}
+
+/// Check that `input` is a single valid sNBT value, without building
+/// anything from it. This is cheaper than deserializing a [`Value`] only to
+/// throw it away. Any input after the value other than whitespace is an
+/// error, whose [`position`](error::Error::position) is where it starts.
+///
+/// ```
+/// assert!(fastsnbt::validate("{a: [1, 2], b: 'c'}").is_ok());
+///
+/// let err = fastsnbt::validate("{a: 1} {b: 2}").unwrap_err();
+/// assert_eq!(7, err.position().unwrap().offset);
+/// ```
+#[cfg(feature = "std")]
+pub fn validate(input: &str) -> Result<()> {
+    validate_with_opts(input, DeOpts::new())
+}
+
+/// Check that `input` is a single valid sNBT value, with the given options.
+/// See [`validate`].
+///
+/// ```
+/// # use fastsnbt::DeOpts;
+/// let opts = DeOpts::new().strict_lists(true);
+/// assert!(fastsnbt::validate_with_opts("[1b, 2s]", opts).is_err());
+/// ```
+#[cfg(feature = "std")]
+pub fn validate_with_opts(input: &str, opts: DeOpts) -> Result<()> {
+    from_str_with_opts::<serde::de::IgnoredAny>(input, opts).map(|_| ())
+}
+
+/// Deserialize into a `T` from sNBT in a byte slice, such as the contents of a
+/// file. The bytes are checked to be UTF-8 once and then parsed in place, so
+/// `T` can borrow from them as with [`from_str`]. Bytes that are not valid
+/// UTF-8 are an error, positioned at the first invalid byte.
+///
+/// ```
+/// # use fastnbt::Value;
+/// let value: Value = fastsnbt::from_slice(b"{a: 1b}").unwrap();
+/// assert_eq!(fastnbt::nbt!({"a": 1i8}), value);
+/// ```
+#[cfg(feature = "std")]
+pub fn from_slice<'a, T: Deserialize<'a>>(input: &'a [u8]) -> Result<T> {
+    from_slice_with_opts(input, DeOpts::new())
+}
+
+/// Deserialize into a `T` from sNBT in a byte slice. See [`from_slice`] for
+/// more information. The options allow you to make the deserializer stricter
+/// than the game is.
+#[cfg(feature = "std")]
+pub fn from_slice_with_opts<'a, T: Deserialize<'a>>(input: &'a [u8], opts: DeOpts) -> Result<T> {
+    let input = core::str::from_utf8(input).map_err(|e| {
+        // Everything before the error is valid, so can be used to find the
+        // line and column.
+        let valid = core::str::from_utf8(&input[..e.valid_up_to()]).unwrap_or_default();
+        error::Error::invalid_utf8(e).with_position(Position::new(valid, valid.len()))
+    })?;
+    from_str_with_opts(input, opts)
+}
+
+/// Deserialize into a `T` from sNBT read from a reader. See the [`de`] module
+/// for more information.
+///
+/// The parser works on a complete string, so the whole of the reader is read
+/// into memory before anything is deserialized. This means `T` cannot borrow
+/// from the input. Input that is not valid UTF-8 is an error.
+///
+/// ```
+/// # use fastnbt::Value;
+/// let reader = std::io::Cursor::new(b"{a: 1b}");
+/// let value: Value = fastsnbt::from_reader(reader).unwrap();
+/// assert_eq!(fastnbt::nbt!({"a": 1i8}), value);
+/// ```
+#[cfg(feature = "std")]
+pub fn from_reader<R: Read, T: DeserializeOwned>(reader: R) -> Result<T> {
+    from_reader_with_opts(reader, DeOpts::new())
+}
+
+/// Deserialize into a `T` from sNBT read from a reader. See [`from_reader`]
+/// for more information. The options allow you to make the deserializer
+/// stricter than the game is.
+#[cfg(feature = "std")]
+pub fn from_reader_with_opts<R: Read, T: DeserializeOwned>(
+    mut reader: R,
+    opts: DeOpts,
+) -> Result<T> {
+    let mut input = String::new();
+    reader.read_to_string(&mut input)?;
+    from_str_with_opts(&input, opts)
+}
+
+/// Options for customizing deserialization. By default the deserializer is
+/// as lenient as the game.
+#[cfg(feature = "std")]
+#[derive(Clone)]
+pub struct DeOpts {
+    /// Whether a compound repeating a key is an error.
+    deny_duplicate_keys: bool,
+    /// Whether a list mixing element types is an error.
+    strict_lists: bool,
+    /// How deeply lists and compounds may be nested.
+    max_depth: usize,
+    /// Whether a comma may follow the last element of a collection.
+    allow_trailing_commas: bool,
+    /// Whether integers may be written in hex or binary.
+    radix_integers: bool,
+    /// Whether `true` and `false` may be in any case.
+    case_insensitive_bools: bool,
+    /// How many list and array elements may be parsed in total.
+    max_elements: Option<usize>,
+    /// Which edition's sNBT to read.
+    dialect: Dialect,
+}
+
+#[cfg(feature = "std")]
+impl Default for DeOpts {
+    fn default() -> Self {
+        Self {
+            deny_duplicate_keys: false,
+            strict_lists: false,
+            max_depth: 256,
+            allow_trailing_commas: false,
+            radix_integers: false,
+            case_insensitive_bools: false,
+            max_elements: None,
+            dialect: Dialect::Java,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl DeOpts {
+    /// Create new options. This object follows a builder pattern.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Set whether a compound containing the same key more than once is an
+    /// error. The game keeps the last value, which is also what happens when
+    /// this is off.
+    pub fn deny_duplicate_keys(mut self, value: bool) -> Self {
+        self.deny_duplicate_keys = value;
+        self
+    }
+
+    /// Set whether a list whose elements are not all of the same tag type,
+    /// such as `[1b,2s]`, is an error. NBT lists are homogeneous, but by
+    /// default mixed lists are accepted.
+    pub fn strict_lists(mut self, value: bool) -> Self {
+        self.strict_lists = value;
+        self
+    }
+
+    /// Set how deeply lists and compounds may be nested, 256 by default.
+    /// Deeper input is an error. The default fits within the 2 MiB stack of a
+    /// spawned thread, even in debug builds. Each level uses stack, so a
+    /// higher limit can overflow it instead of returning an error. The game
+    /// allows 512 levels, which in debug builds can need a bigger stack.
+    pub fn max_depth(mut self, value: usize) -> Self {
+        self.max_depth = value;
+        self
+    }
+
+    /// Set whether a comma is allowed straight before the end of a list,
+    /// array or compound, like `[1,2,]`. The game rejects these, but they are
+    /// easy to leave behind when editing sNBT by hand.
+    pub fn allow_trailing_commas(mut self, value: bool) -> Self {
+        self.allow_trailing_commas = value;
+        self
+    }
+
+    /// Set whether integers may be written in hexadecimal like `0x1F`, or in
+    /// binary like `-0b101s`, as some modded tooling does. The usual type
+    /// suffixes apply, except that hex bytes cannot be written since `b` is a
+    /// hex digit. A value too large for its type is an error. By default
+    /// these are read as unquoted strings, like the game does.
+    pub fn radix_integers(mut self, value: bool) -> Self {
+        self.radix_integers = value;
+        self
+    }
+
+    /// Set whether booleans are recognised in any case, like `True` or
+    /// `FALSE`. By default only lowercase `true` and `false` are booleans,
+    /// and other cases are read as unquoted strings.
+    pub fn case_insensitive_bools(mut self, value: bool) -> Self {
+        self.case_insensitive_bools = value;
+        self
+    }
+
+    /// Set how many list and array elements may be parsed in total, counting
+    /// those of every list and array in the input. More is an error, which
+    /// guards against untrusted input like a huge `[I;...]` using up memory.
+    /// Together with [`max_depth`](Self::max_depth) this bounds the work done
+    /// for any input. By default, with `None`, there is no limit.
+    pub fn max_elements(mut self, value: Option<usize>) -> Self {
+        self.max_elements = value;
+        self
+    }
+
+    /// Set which edition's sNBT to read, [`Dialect::Java`] by default. This
+    /// leaves the other options as they are, since none of them are part of
+    /// either edition's sNBT.
+    ///
+    /// ```
+    /// # use fastsnbt::{DeOpts, Dialect};
+    /// let opts = DeOpts::new().dialect(Dialect::Bedrock);
+    /// assert_eq!("+5", fastsnbt::from_str_with_opts::<String>("+5", opts).unwrap());
+    /// ```
+    pub fn dialect(mut self, value: Dialect) -> Self {
+        self.dialect = value;
+        self
+    }
+}
+
+/// Parse a single sNBT value from the start of `input`, returning the value
+/// and the rest of the input following it. Unlike [`from_str`] this does not
+/// require the entire input to be consumed, so it can be used to parse a
+/// fragment out of a larger string.
+///
+/// ```
+/// # use fastnbt::Value;
+/// let (value, rest) = fastsnbt::parse_value("[I;1,2] and more").unwrap();
+/// assert_eq!(Value::IntArray(fastnbt::IntArray::new(vec![1, 2])), value);
+/// assert_eq!("and more", rest);
+/// ```
+#[cfg(feature = "std")]
+pub fn parse_value(input: &str) -> Result<(Value, &str)> {
+    from_str_prefix(input)
+}
+
+/// Deserialize a [`Value`] from some sNBT data, along with statistics of how
+/// many values of each tag it holds and how deeply it nests. This is for
+/// tooling that analyses sNBT, the counting is done as the input is parsed.
+///
+/// ```
+/// # use fastnbt::Tag;
+/// let (_, stats) = fastsnbt::from_str_with_stats("{a: [1b, 2b], b: 'c'}").unwrap();
+/// assert_eq!(2, stats.count(Tag::Byte));
+/// assert_eq!(1, stats.count(Tag::String));
+/// assert_eq!(2, stats.max_depth());
+/// ```
+#[cfg(feature = "std")]
+pub fn from_str_with_stats(input: &str) -> Result<(Value, ParseStats)> {
     let mut des = Deserializer::from_str(input);
-    let t = T::deserialize(&mut des)?;
-    if !des.input.is_empty() {
-        return Err(error::Error::input_not_consumed());
+    des.begin()?;
+    des.stats = Some(ParseStats::default());
+    let value = Value::deserialize(&mut des).map_err(|e| e.with_position(des.position()))?;
+    des.end()?;
+    Ok((value, des.stats.unwrap_or_default()))
+}
+
+/// Whether `s` can be written without quotes as a compound key and read back
+/// as the same key. That is when it is not empty and is made up of only ASCII
+/// letters, digits and `_-.+`. This is the rule [`SerOpts::unquoted_keys`]
+/// uses to decide which keys to quote.
+///
+/// This only holds for keys. As a value, an unquoted string like `123` or
+/// `true` is read back as a number or a bool instead.
+///
+/// ```
+/// assert!(fastsnbt::is_simple_string("minecraft.stone_1"));
+/// assert!(fastsnbt::is_simple_string("123"));
+/// assert!(!fastsnbt::is_simple_string("minecraft:stone"));
+/// assert!(!fastsnbt::is_simple_string(""));
+/// ```
+pub fn is_simple_string(s: &str) -> bool {
+    parser::is_simple_string(s)
+}
+
+/// Parse a single sNBT string from the start of `input`, returning the
+/// string, how it was quoted, and the rest of the input following it. This
+/// lets a formatter keep the quotes the input was written with.
+///
+/// ```
+/// # use fastsnbt::QuoteStyle;
+/// let (s, style, rest) = fastsnbt::parse_str_with_meta("'it\\'s' and more").unwrap();
+/// assert_eq!("it's", s);
+/// assert_eq!(QuoteStyle::Single, style);
+/// assert_eq!(" and more", rest);
+/// ```
+#[cfg(feature = "std")]
+pub fn parse_str_with_meta(input: &str) -> Result<(Cow<'_, str>, QuoteStyle, &str)> {
+    let start = input.trim_start();
+    match parser::parse_str_with_meta(start) {
+        Ok((rest, (s, style))) => Ok((s, style, rest)),
+        Err(nom::Err::Failure(e)) if e.code == nom::error::ErrorKind::Eof => {
+            let offset = input.len() - e.input.len();
+            Err(error::Error::trailing_backslash().with_position(Position::new(input, offset)))
+        }
+        Err(nom::Err::Error(e) | nom::Err::Failure(e)) => {
+            let offset = input.len() - e.input.len();
+            Err(error::Error::invalid_input().with_position(Position::new(input, offset)))
+        }
+        Err(nom::Err::Incomplete(_)) => Err(error::Error::unexpected_eof()),
     }
-    Ok(t)
+}
+
+/// Deserialize a [`LosslessValue`] from sNBT, keeping the text of any number
+/// not written the way this crate would write it, such as `1.50f`, `+5` or
+/// `1B`. Writing the value back out with [`to_string`] reproduces those
+/// numbers exactly, so editing one field leaves the rest of the numbers as
+/// they were. This requires the `lossless` feature.
+///
+/// ```
+/// # use fastnbt::Value;
+/// use fastsnbt::LosslessValue;
+///
+/// let mut value = fastsnbt::from_str_lossless("{a: 1.50f, b: +5}").unwrap();
+/// if let LosslessValue::Compound(map) = &mut value {
+///     map.insert("b".into(), Value::Int(6).into());
+/// }
+/// assert_eq!(r#"{"a":1.50f,"b":6}"#, fastsnbt::to_string(&value).unwrap());
+/// ```
+#[cfg(feature = "lossless")]
+pub fn from_str_lossless(input: &str) -> Result<LosslessValue> {
+    let mut des = Deserializer::from_str(input);
+    des.begin()?;
+    let value = des
+        .lossless_value()
+        .map_err(|e| e.with_position(des.position()))?;
+    des.end()?;
+    Ok(value)
+}
+
+/// Deserialize the elements of a sNBT list one at a time, without collecting
+/// them. This suits huge lists that are only iterated over. Elements can
+/// borrow from the input like with [`from_str`].
+///
+/// ```
+/// let sum: i32 = fastsnbt::list_iter::<i32>("[1, 2, 3]")
+///     .map(|n| n.unwrap())
+///     .sum();
+/// assert_eq!(6, sum);
+/// ```
+#[cfg(feature = "std")]
+pub fn list_iter<'de, T: Deserialize<'de>>(input: &'de str) -> ListIter<'de, T> {
+    list_iter_with_opts(input, DeOpts::new())
+}
+
+/// Deserialize the elements of a sNBT list one at a time. See [`list_iter`]
+/// for more information. The options allow you to set things like strict
+/// lists.
+#[cfg(feature = "std")]
+pub fn list_iter_with_opts<'de, T: Deserialize<'de>>(
+    input: &'de str,
+    opts: DeOpts,
+) -> ListIter<'de, T> {
+    ListIter::new(input, opts)
 }
 
 /// Serialize some `T` into some sNBT string. This produces
 /// valid utf-8. See the [`ser`] module for more information.
 pub fn to_vec<T: ?Sized + Serialize>(value: &T) -> Result<Vec<u8>> {
-    let mut serializer = Serializer {
-        writer: Vec::new(),
-        indent: None,
-    };
-    value.serialize(&mut serializer)?;
-    Ok(serializer.writer)
+    to_vec_with_opts(value, SerOpts::new())
 }
 
 /// Serialize some `T` into a sNBT string. See the [`ser`]
 /// module for more information.
 pub fn to_string<T: ?Sized + Serialize>(value: &T) -> Result<String> {
-    let vec = to_vec(value)?;
-    let string = unsafe {
-        // We do not emit invalid UTF-8.
-        String::from_utf8_unchecked(vec)
-    };
-    Ok(string)
+    to_string_with_opts(value, SerOpts::new())
 }
 
 /// Serialize some `T` into some sNBT string. This produces
 /// valid utf-8. See the [`ser`] module for more information.
 pub fn to_vec_pretty<T: ?Sized + Serialize>(value: &T) -> Result<Vec<u8>> {
-    let mut serializer = Serializer {
-        writer: Vec::new(),
-        indent: Some(0),
-    };
-    value.serialize(&mut serializer)?;
-    Ok(serializer.writer)
+    to_vec_with_opts(value, SerOpts::pretty())
 }
 
 /// Serialize some `T` into a sNBT string. See the [`ser`]
 /// module for more information.
 pub fn to_string_pretty<T: ?Sized + Serialize>(value: &T) -> Result<String> {
-    let vec = to_vec_pretty(value)?;
-    let string = unsafe {
-        // We do not emit invalid UTF-8.
-        String::from_utf8_unchecked(vec)
-    };
-    Ok(string)
+    to_string_with_opts(value, SerOpts::pretty())
+}
+
+/// Serialize some `T` into a pretty printed sNBT string, as if it were nested
+/// `level` levels deep. Every line after the first is indented by `level`
+/// extra units, so the output lines up when embedded in a larger document.
+///
+/// ```
+/// # use std::collections::BTreeMap;
+/// let snbt = fastsnbt::to_string_pretty_at(&BTreeMap::from([("a", 1)]), 1).unwrap();
+/// assert_eq!("{\n        \"a\": 1\n    }", snbt);
+/// ```
+pub fn to_string_pretty_at<T: ?Sized + Serialize>(value: &T, level: usize) -> Result<String> {
+    let mut serializer = Serializer::pretty(Vec::new());
+    serializer.indent = Some(level);
+    value.serialize(&mut serializer)?;
+    Ok(into_string(serializer.into_inner()))
+}
+
+/// Serialize a [`Value`] into a sNBT string. The output is the same as
+/// [`to_string_with_opts`], but this is faster as it walks the value directly
+/// rather than going through serde. This only fails if the output is larger
+/// than [`SerOpts::max_output_bytes`].
+///
+/// ```
+/// # use fastsnbt::SerOpts;
+/// let value = fastnbt::nbt!({"name": "Steve", "pos": [1, 2, 3]});
+/// let snbt = fastsnbt::value_to_snbt(&value, SerOpts::new()).unwrap();
+/// assert_eq!(r#"{"name":"Steve","pos":[1,2,3]}"#, snbt);
+/// ```
+#[cfg(feature = "std")]
+pub fn value_to_snbt(value: &Value, opts: SerOpts) -> Result<String> {
+    let mut serializer = Serializer::with_opts(Vec::new(), opts);
+    serializer.write_value(value)?;
+    if serializer.opts.trailing_newline {
+        serializer.writer.write_all(b"\n")?;
+    }
+    Ok(into_string(serializer.into_inner()))
+}
+
+/// The unit of indentation written per level when pretty printing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Indent {
+    /// Indent with the given number of spaces per level.
+    Spaces(usize),
+    /// Indent with a single tab per level.
+    Tabs,
+}
+
+/// The quote character used when writing strings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quoting {
+    /// Always quote with `"`.
+    Double,
+    /// Always quote with `'`.
+    Single,
+    /// Quote with whichever of `"` or `'` needs the fewest escapes, preferring
+    /// `"` on a tie.
+    Minimal,
+}
+
+/// Which edition of the game's sNBT to read and write. Java is the default,
+/// and is what everything else in this crate describes. Bedrock differs in
+/// these ways:
+///
+/// * Numbers cannot have a `+` sign, so `+5` is the unquoted string `"+5"`
+///   rather than the int `5`.
+/// * The only escapes in quoted strings are `\\`, `\"` and `\'`. Others,
+///   like `\n` and `\u00e9`, are an error when deserializing, and when
+///   serializing newlines and other control characters are written as they
+///   are.
+/// * Bytes may be written unsigned, from `128b` to `255b`. These wrap to the
+///   byte with the same bits, so `200b` reads as `-56b`. Bytes are still
+///   serialized signed.
+///
+/// A dialect only covers how the editions themselves differ. It is not a
+/// preset for the other [`DeOpts`], like
+/// [`allow_trailing_commas`](DeOpts::allow_trailing_commas) or
+/// [`radix_integers`](DeOpts::radix_integers): neither edition reads those
+/// extensions, so they are set on their own and are off for both.
+///
+/// Set it with [`DeOpts::dialect`] and [`SerOpts::dialect`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialect {
+    /// sNBT as Java Edition reads and writes it.
+    Java,
+    /// sNBT as Bedrock Edition reads and writes it.
+    Bedrock,
+}
+
+/// How a string was written in sNBT, as returned by [`parse_str_with_meta`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuoteStyle {
+    /// A bare string like `foo`, with no quotes.
+    Unquoted,
+    /// Quoted with `'`.
+    Single,
+    /// Quoted with `"`.
+    Double,
+}
+
+/// Options for customizing serialization.
+#[derive(Clone)]
+pub struct SerOpts {
+    /// Whether to spread the output over multiple indented lines.
+    pretty: bool,
+    /// What is written per indent level when pretty printing.
+    indent: Indent,
+    /// Whether compound keys are written in sorted order.
+    sort_keys: bool,
+    /// The quote character used for strings.
+    quoting: Quoting,
+    /// Whether compound keys are left unquoted where they can be.
+    unquoted_keys: bool,
+    /// Whether number type suffixes are uppercase.
+    uppercase_suffixes: bool,
+    /// Whether bools are written as the bytes `1b` and `0b`.
+    bools_as_bytes: bool,
+    /// Whether compound entries holding an empty list are left out.
+    skip_empty_seqs: bool,
+    /// Whether to write no whitespace at all, overriding `pretty`.
+    minify: bool,
+    /// Whether a newline follows the value.
+    trailing_newline: bool,
+    /// Whether the writer is flushed once the value is written.
+    auto_flush: bool,
+    /// How many decimal places floats are written with, or `None` for the
+    /// shortest representation that reads back the same.
+    float_precision: Option<usize>,
+    /// The most bytes that may be written, or `None` for no limit.
+    max_output_bytes: Option<usize>,
+    /// Which edition's sNBT to write.
+    dialect: Dialect,
+}
+
+impl Default for SerOpts {
+    fn default() -> Self {
+        Self {
+            pretty: false,
+            indent: Indent::Spaces(4),
+            sort_keys: false,
+            quoting: Quoting::Double,
+            unquoted_keys: false,
+            uppercase_suffixes: false,
+            bools_as_bytes: false,
+            skip_empty_seqs: false,
+            minify: false,
+            trailing_newline: false,
+            auto_flush: false,
+            float_precision: None,
+            max_output_bytes: None,
+            dialect: Dialect::Java,
+        }
+    }
+}
+
+impl SerOpts {
+    /// Create new options for compact output. This object follows a builder
+    /// pattern.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Create new options for pretty printed output, as used by
+    /// [`to_string_pretty`].
+    pub fn pretty() -> Self {
+        Self {
+            pretty: true,
+            ..Default::default()
+        }
+    }
+
+    /// Set what is written per indent level, four spaces by default. This
+    /// only has an effect when pretty printing.
+    pub fn indent(mut self, indent: Indent) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Set whether compound keys are written in sorted order rather than the
+    /// order they are serialized in. Keys are compared by their serialized
+    /// form. This is useful to get deterministic output from types like
+    /// `HashMap`.
+    pub fn sort_keys(mut self, sort_keys: bool) -> Self {
+        self.sort_keys = sort_keys;
+        self
+    }
+
+    /// Set the quote character used for strings and compound keys, `"` by
+    /// default. Only the quote character in use and backslashes need to be
+    /// escaped.
+    pub fn quoting(mut self, quoting: Quoting) -> Self {
+        self.quoting = quoting;
+        self
+    }
+
+    /// Set whether compound keys are written without quotes when they are
+    /// made up of only ASCII letters, digits and `_-.+`, as Minecraft writes
+    /// them, eg `{id:"minecraft:stone",Count:1b}`. Other keys, including the
+    /// empty key, are quoted as usual. Keys are always read back as strings,
+    /// even one like `1` or `true`.
+    pub fn unquoted_keys(mut self, unquoted_keys: bool) -> Self {
+        self.unquoted_keys = unquoted_keys;
+        self
+    }
+
+    /// Set whether number type suffixes are written in uppercase, eg `1B` and
+    /// `2L` rather than `1b` and `2l`. Both are read back the same.
+    pub fn uppercase_suffixes(mut self, uppercase_suffixes: bool) -> Self {
+        self.uppercase_suffixes = uppercase_suffixes;
+        self
+    }
+
+    /// Set whether bools are written as the bytes `1b` and `0b`, which is how
+    /// NBT stores them, rather than as `true` and `false`. Both are read back
+    /// the same.
+    pub fn bools_as_bytes(mut self, bools_as_bytes: bool) -> Self {
+        self.bools_as_bytes = bools_as_bytes;
+        self
+    }
+
+    /// Set whether compound entries whose value is an empty list, such as an
+    /// empty `Vec` field, are left out like `None` ones, as vanilla leaves out
+    /// many empty tags. Off by default. Empty lists elsewhere, and empty
+    /// typed arrays like `[I;]`, are still written.
+    ///
+    /// ```
+    /// # use fastsnbt::SerOpts;
+    /// #[derive(serde::Serialize)]
+    /// struct Entity {
+    ///     id: &'static str,
+    ///     #[serde(rename = "Tags")]
+    ///     tags: Vec<String>,
+    /// }
+    ///
+    /// let entity = Entity { id: "minecraft:pig", tags: vec![] };
+    /// let opts = SerOpts::new().skip_empty_seqs(true);
+    /// assert_eq!(
+    ///     r#"{"id":"minecraft:pig"}"#,
+    ///     fastsnbt::to_string_with_opts(&entity, opts).unwrap()
+    /// );
+    /// ```
+    pub fn skip_empty_seqs(mut self, skip_empty_seqs: bool) -> Self {
+        self.skip_empty_seqs = skip_empty_seqs;
+        self
+    }
+
+    /// Set whether the output is minified, containing no whitespace outside
+    /// of strings even if these options are otherwise for pretty printing.
+    pub fn minify(mut self, minify: bool) -> Self {
+        self.minify = minify;
+        self
+    }
+
+    /// Set whether a newline is written after the value, as is usual for a
+    /// text file. This is only written by functions like [`to_writer`] and
+    /// [`to_string`], not by a [`Serializer`] itself, so serializing several
+    /// values with one serializer does not separate them.
+    pub fn trailing_newline(mut self, trailing_newline: bool) -> Self {
+        self.trailing_newline = trailing_newline;
+        self
+    }
+
+    /// Set whether [`to_writer`] and similar functions flush the writer once
+    /// the value is written, so that a [`BufWriter`](std::io::BufWriter) holds
+    /// nothing back and any error writing it is returned. Off by default. This
+    /// does nothing for a [`Serializer`] used directly, which has
+    /// [`Serializer::flush`] instead.
+    pub fn auto_flush(mut self, auto_flush: bool) -> Self {
+        self.auto_flush = auto_flush;
+        self
+    }
+
+    /// Set how many decimal places floats and doubles are written with, for
+    /// example `Some(3)` to write coordinates like `12.500d`. By default, with
+    /// `None`, the shortest representation that reads back as the same value is
+    /// written. A fixed precision may lose information.
+    pub fn float_precision(mut self, float_precision: Option<usize>) -> Self {
+        self.float_precision = float_precision;
+        self
+    }
+
+    /// Set the most bytes that may be written, after which serialization
+    /// fails with an error. This guards against huge output when serializing
+    /// untrusted data, such as binary NBT holding large arrays. Output already
+    /// written to the writer before the error is not undone. By default, with
+    /// `None`, there is no limit.
+    pub fn max_output_bytes(mut self, max_output_bytes: Option<usize>) -> Self {
+        self.max_output_bytes = max_output_bytes;
+        self
+    }
+
+    /// Set which edition's sNBT to write, [`Dialect::Java`] by default.
+    ///
+    /// ```
+    /// # use fastsnbt::{Dialect, SerOpts};
+    /// let opts = SerOpts::new().dialect(Dialect::Bedrock);
+    /// assert_eq!("\"a\nb\"", fastsnbt::to_string_with_opts("a\nb", opts).unwrap());
+    /// ```
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// Whether output is pretty printed, spread over indented lines with a
+    /// space after the colon of each compound key. Minifying turns this off.
+    pub(crate) fn is_pretty(&self) -> bool {
+        self.pretty && !self.minify
+    }
+}
+
+/// Serialize some `T` into some sNBT string. This produces
+/// valid utf-8. See the [`ser`] module for more information.
+/// The options allow you to set things like the indentation.
+pub fn to_vec_with_opts<T: ?Sized + Serialize>(value: &T, opts: SerOpts) -> Result<Vec<u8>> {
+    let mut vec = Vec::new();
+    to_writer_with_opts(&mut vec, value, opts)?;
+    Ok(vec)
+}
+
+/// Serialize some `T` as sNBT into a writer. See the [`ser`] module for more
+/// information. Writes are not buffered, so a
+/// [`BufWriter`](std::io::BufWriter) may be worthwhile for files and sockets.
+/// The writer is not flushed by default, so a `BufWriter` dropped without
+/// flushing loses any error from its final write. Set
+/// [`SerOpts::auto_flush`] to flush it once the value is written.
+pub fn to_writer<W: Write, T: ?Sized + Serialize>(writer: W, value: &T) -> Result<()> {
+    to_writer_with_opts(writer, value, SerOpts::new())
+}
+
+/// Serialize some `T` as pretty printed sNBT into a writer. See
+/// [`to_writer`] for more information.
+pub fn to_writer_pretty<W: Write, T: ?Sized + Serialize>(writer: W, value: &T) -> Result<()> {
+    to_writer_with_opts(writer, value, SerOpts::pretty())
+}
+
+/// Serialize some `T` as sNBT into a writer. See [`to_writer`] for more
+/// information. The options allow you to set things like the indentation.
+pub fn to_writer_with_opts<W: Write, T: ?Sized + Serialize>(
+    writer: W,
+    value: &T,
+    opts: SerOpts,
+) -> Result<()> {
+    let mut serializer = Serializer::with_opts(writer, opts);
+    value.serialize(&mut serializer)?;
+    if serializer.opts.trailing_newline {
+        serializer.writer.write_all(b"\n")?;
+    }
+    if serializer.opts.auto_flush {
+        serializer.flush()?;
+    }
+    Ok(())
+}
+
+/// Start writing a compound into a writer one field at a time, rather than
+/// serializing a single value. See [`CompoundWriter`](ser::CompoundWriter).
+///
+/// ```
+/// let mut out = Vec::new();
+/// let mut compound = fastsnbt::compound_writer(&mut out).unwrap();
+/// compound.field("x", &1).unwrap();
+/// compound.field("y", "two").unwrap();
+/// compound.finish().unwrap();
+/// assert_eq!(br#"{"x":1,"y":"two"}"#, out.as_slice());
+/// ```
+pub fn compound_writer<W: Write>(writer: W) -> Result<ser::CompoundWriter<W>> {
+    compound_writer_with_opts(writer, SerOpts::new())
+}
+
+/// Start writing a compound into a writer one field at a time, with the given
+/// options. See [`compound_writer`].
+pub fn compound_writer_with_opts<W: Write>(
+    writer: W,
+    opts: SerOpts,
+) -> Result<ser::CompoundWriter<W>> {
+    ser::CompoundWriter::new(Serializer::with_opts(writer, opts))
+}
+
+/// Serialize some `T` as sNBT into a [`fmt::Write`], such as a `String` or a
+/// [`fmt::Formatter`]. See the [`ser`] module for more information.
+///
+/// ```
+/// use std::fmt::Write;
+///
+/// let mut out = String::from("data: ");
+/// fastsnbt::to_fmt_writer(&mut out, &[1i8, 2]).unwrap();
+/// assert_eq!("data: [1b,2b]", out);
+/// ```
+pub fn to_fmt_writer<W: fmt::Write, T: ?Sized + Serialize>(writer: W, value: &T) -> Result<()> {
+    to_fmt_writer_with_opts(writer, value, SerOpts::new())
+}
+
+/// Serialize some `T` as sNBT into a [`fmt::Write`]. See [`to_fmt_writer`]
+/// for more information. The options allow you to set things like the
+/// indentation.
+pub fn to_fmt_writer_with_opts<W: fmt::Write, T: ?Sized + Serialize>(
+    mut writer: W,
+    value: &T,
+    opts: SerOpts,
+) -> Result<()> {
+    to_writer_with_opts(FmtWriter(&mut writer), value, opts)
+}
+
+/// Serialize some `T` into a sNBT string. See the [`ser`]
+/// module for more information. The options allow you to set things
+/// like the indentation.
+pub fn to_string_with_opts<T: ?Sized + Serialize>(value: &T, opts: SerOpts) -> Result<String> {
+    Ok(into_string(to_vec_with_opts(value, opts)?))
+}
+
+/// Take the bytes written by a [`Serializer`] as a `String`, without copying
+/// them. Checking them once here is faster than serializing into a `String`
+/// through [`to_fmt_writer`], which goes through `fmt` for every write.
+fn into_string(snbt: Vec<u8>) -> String {
+    // The serializer only writes whole `str`s, ASCII punctuation and escapes,
+    // and the ASCII digits of numbers.
+    String::from_utf8(snbt).expect("serializer wrote invalid UTF-8")
+}
+
+/// Displays the wrapped value as sNBT, compact like [`to_string`] with `{}`
+/// and pretty printed like [`to_string_pretty`] with `{:#}`. Serialization
+/// errors become a [`fmt::Error`], so formatting a value that cannot be
+/// serialized panics in methods like [`ToString::to_string`]. Use those
+/// functions to handle the error instead.
+///
+/// [`Value`] is defined in `fastnbt`, which does not depend on this crate, so
+/// it cannot implement `Display` as sNBT itself. Wrap it in this instead, or
+/// use [`ValueExt::snbt`]:
+///
+#[cfg_attr(feature = "std", doc = "```")]
+#[cfg_attr(not(feature = "std"), doc = "```ignore")]
+/// use fastnbt::nbt;
+/// use fastsnbt::Snbt;
+///
+/// let v = nbt!({"a": 1});
+/// assert_eq!("{\"a\":1}", format!("{}", Snbt(&v)));
+/// assert_eq!("{\n    \"a\": 1\n}", format!("{:#}", Snbt(&v)));
+/// ```
+pub struct Snbt<'a, T: ?Sized + Serialize>(pub &'a T);
+
+impl<T: ?Sized + Serialize> fmt::Display for Snbt<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let opts = if f.alternate() {
+            SerOpts::pretty()
+        } else {
+            SerOpts::new()
+        };
+        to_fmt_writer_with_opts(f, self.0, opts).map_err(|_| fmt::Error)
+    }
+}
+
+/// Adapts a [`fmt::Write`] to a [`Write`]. Each write must be valid UTF-8 on
+/// its own, which holds for everything the serializer writes.
+struct FmtWriter<'a, F: fmt::Write>(&'a mut F);
+
+impl<F: fmt::Write> Write for FmtWriter<'_, F> {
+    fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+        let s = core::str::from_utf8(buf)
+            .map_err(|e| Error::bespoke(format!("wrote invalid UTF-8: {e}")))?;
+        self.0.write_str(s).map_err(|_| Error::formatter_failed())
+    }
 }
@@ -0,0 +1,77 @@
+use std::collections::BTreeMap;
+
+use fastnbt::Value;
+use serde::Serialize;
+
+use crate::ser::{RAW_TEXT, RAW_VALUE_TOKEN};
+
+/// A [`Value`] from [`from_str_lossless`](crate::from_str_lossless), which
+/// keeps the text of any number not written the way this crate would write it.
+/// Serializing it with this crate writes those numbers back out exactly as
+/// they were, even inside a struct. Other formats, such as fastnbt or JSON,
+/// get the plain values.
+///
+/// Numbers in arrays are not kept, nor is other formatting such as whitespace
+/// and quoting.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LosslessValue {
+    /// A number and the text it was parsed from, such as `1.50f` or `+5`.
+    Raw {
+        value: Value,
+        text: String,
+    },
+    List(Vec<LosslessValue>),
+    Compound(BTreeMap<String, LosslessValue>),
+    /// Any other value, which has no text to keep.
+    Value(Value),
+}
+
+impl LosslessValue {
+    /// Convert into a plain [`Value`], dropping the text of numbers.
+    pub fn into_value(self) -> Value {
+        match self {
+            LosslessValue::Raw { value, .. } | LosslessValue::Value(value) => value,
+            LosslessValue::List(list) => {
+                Value::List(list.into_iter().map(LosslessValue::into_value).collect())
+            }
+            LosslessValue::Compound(map) => {
+                Value::Compound(map.into_iter().map(|(k, v)| (k, v.into_value())).collect())
+            }
+        }
+    }
+}
+
+impl From<Value> for LosslessValue {
+    fn from(value: Value) -> Self {
+        LosslessValue::Value(value)
+    }
+}
+
+impl From<LosslessValue> for Value {
+    fn from(value: LosslessValue) -> Self {
+        value.into_value()
+    }
+}
+
+impl Serialize for LosslessValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            LosslessValue::Raw { value, text } => {
+                // Serde has no way to pass the text along with the value, so
+                // it is left for this crate's serializer to pick up when it
+                // sees the newtype. Any other serializer writes the value, and
+                // the text is cleared after in case it was not taken.
+                RAW_TEXT.set(Some(text.clone()));
+                let result = serializer.serialize_newtype_struct(RAW_VALUE_TOKEN, value);
+                RAW_TEXT.take();
+                result
+            }
+            LosslessValue::List(list) => list.serialize(serializer),
+            LosslessValue::Compound(map) => map.serialize(serializer),
+            LosslessValue::Value(value) => value.serialize(serializer),
+        }
+    }
+}
@@ -1,134 +1,372 @@
-use std::borrow::Cow;
+use alloc::{borrow::Cow, string::String};
+use core::{
+    num::{IntErrorKind, ParseIntError},
+    str::{Chars, FromStr},
+};
 
+use memchr::memchr2;
 use nom::{
     branch::alt,
-    bytes::complete::{is_a, tag, tag_no_case},
+    bytes::complete::{is_a, tag, tag_no_case, take_while1},
     character::complete::{alphanumeric1, char, digit0, digit1, one_of},
     combinator::{cut, map, map_res, opt, recognize},
     error::{ErrorKind, ParseError},
     multi::many1,
-    sequence::{delimited, pair, tuple},
+    sequence::{delimited, pair, terminated, tuple},
     IResult,
 };
 
+use crate::{Dialect, QuoteStyle};
+
 pub fn parse_str(input: &str) -> IResult<&str, Cow<'_, str>> {
+    map(parse_str_with_meta, |(s, _)| s)(input)
+}
+
+/// Parse a string like [`parse_str`], along with how it was quoted.
+pub fn parse_str_with_meta(input: &str) -> IResult<&str, (Cow<'_, str>, QuoteStyle)> {
+    parse_dialect_str_with_meta(Dialect::Java, input)
+}
+
+/// Parse a string like [`parse_str`], but with only the escapes Bedrock has.
+pub fn parse_bedrock_str(input: &str) -> IResult<&str, Cow<'_, str>> {
+    map(
+        |input| parse_dialect_str_with_meta(Dialect::Bedrock, input),
+        |(s, _)| s,
+    )(input)
+}
+
+fn parse_dialect_str_with_meta(
+    dialect: Dialect,
+    input: &str,
+) -> IResult<&str, (Cow<'_, str>, QuoteStyle)> {
     alt((
-        delimited(char('"'), parse_escaped('"'), char('"')),
-        delimited(char('\''), parse_escaped('\''), char('\'')),
-        map(parse_simple_string, Cow::from),
+        map(
+            delimited(char('"'), parse_escaped('"', dialect), char('"')),
+            |s| (s, QuoteStyle::Double),
+        ),
+        map(
+            delimited(char('\''), parse_escaped('\'', dialect), char('\'')),
+            |s| (s, QuoteStyle::Single),
+        ),
+        map(parse_simple_string, |s| {
+            (Cow::from(s), QuoteStyle::Unquoted)
+        }),
     ))(input)
 }
 
+/// Parse the contents of a string quoted with `surround`, up to but not
+/// including the closing quote. This borrows from the input unless there is an
+/// escape, and only allocates once the first escape is found.
 fn parse_escaped<'a, E: ParseError<&'a str>>(
     surround: char,
+    dialect: Dialect,
 ) -> impl FnMut(&'a str) -> IResult<&'a str, Cow<'a, str>, E> {
+    // The quote is ASCII, so cannot match part of a multi-byte character.
+    let quote = surround as u8;
     move |input: &'a str| {
+        let bytes = input.as_bytes();
+        let unclosed = || nom::Err::Error(E::from_error_kind(input, ErrorKind::MapRes));
+
+        let mut end = memchr2(b'\\', quote, bytes).ok_or_else(unclosed)?;
+        if bytes[end] == quote {
+            return Ok((&input[end..], Cow::Borrowed(&input[..end])));
+        }
+
         let mut owned = String::new();
         let mut start = 0;
-        let mut skip = false;
-        let mut chars = input.chars();
-        while let Some(c) = chars.next() {
-            if skip {
-                skip = false;
-                owned.push(c);
-                start = input.len() - chars.as_str().len();
-            } else if c == '\\' {
-                let len = input.len() - chars.as_str().len() - 1;
-                owned.push_str(&input[start..len]);
-                skip = true;
-            } else if c == surround {
-                let len = input.len() - chars.as_str().len() - surround.len_utf8();
-                if !owned.is_empty() {
-                    if len > start {
-                        owned.push_str(&input[start..len]);
-                    }
-                    return Ok((&input[len..], Cow::from(owned)));
-                } else {
-                    return Ok((&input[len..], Cow::from(&input[..len])));
+        while bytes[end] == b'\\' {
+            owned.push_str(&input[start..end]);
+            let mut chars = input[end + 1..].chars();
+            let unescaped = match chars.next() {
+                Some('u') if dialect == Dialect::Java => unescape_unicode(&mut chars),
+                Some(c) => unescape(c, dialect),
+                None => {
+                    return Err(nom::Err::Failure(E::from_error_kind(
+                        &input[end..],
+                        ErrorKind::Eof,
+                    )));
+                }
+            };
+            match unescaped {
+                Some(c) => owned.push(c),
+                None => {
+                    return Err(nom::Err::Failure(E::from_error_kind(
+                        &input[end + 1..],
+                        ErrorKind::Escaped,
+                    )));
                 }
             }
+
+            start = input.len() - chars.as_str().len();
+            end = start + memchr2(b'\\', quote, &bytes[start..]).ok_or_else(unclosed)?;
         }
-        Err(nom::Err::Error(E::from_error_kind(
-            input,
-            ErrorKind::MapRes,
-        )))
+        owned.push_str(&input[start..end]);
+        Ok((&input[end..], Cow::Owned(owned)))
+    }
+}
+
+/// Map the character following a backslash to the character it represents.
+/// Bedrock only escapes backslashes and quotes.
+fn unescape(c: char, dialect: Dialect) -> Option<char> {
+    Some(match c {
+        '\\' | '"' | '\'' => c,
+        _ if dialect == Dialect::Bedrock => return None,
+        'n' => '\n',
+        't' => '\t',
+        'r' => '\r',
+        'b' => '\x08',
+        'f' => '\x0c',
+        '/' => '/',
+        _ => return None,
+    })
+}
+
+/// Decode the `XXXX` of a `\uXXXX` escape. A UTF-16 surrogate pair must be
+/// written as two consecutive escapes, a lone surrogate is invalid.
+fn unescape_unicode(chars: &mut Chars) -> Option<char> {
+    let high = hex4(chars)?;
+    if (0xD800..0xDC00).contains(&high) {
+        if chars.next()? != '\\' || chars.next()? != 'u' {
+            return None;
+        }
+        let low = hex4(chars)?;
+        if !(0xDC00..0xE000).contains(&low) {
+            return None;
+        }
+        char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))
+    } else {
+        // Also rejects a lone low surrogate.
+        char::from_u32(high)
+    }
+}
+
+fn hex4(chars: &mut Chars) -> Option<u32> {
+    let mut n = 0;
+    for _ in 0..4 {
+        n = n * 16 + chars.next()?.to_digit(16)?;
     }
+    Some(n)
 }
 
 fn parse_simple_string(input: &str) -> IResult<&str, &str> {
     recognize(many1(alt((alphanumeric1, is_a("_-.+")))))(input)
 }
 
+/// Whether all of `s` would be read back as an unquoted compound key. A value
+/// like `123` or `true` is read as something other than a string.
+pub(crate) fn is_simple_string(s: &str) -> bool {
+    matches!(parse_simple_string(s), Ok(("", _)))
+}
+
 pub fn parse_bool(input: &str) -> IResult<&str, bool> {
-    alt((map(tag("true"), |_| true), map(tag("false"), |_| false)))(input)
+    whole_token(alt((
+        map(tag("true"), |_| true),
+        map(tag("false"), |_| false),
+    )))(input)
+}
+
+/// Like [`parse_bool`], but in any case, eg `True` or `FALSE`.
+pub fn parse_bool_no_case(input: &str) -> IResult<&str, bool> {
+    whole_token(alt((
+        map(tag_no_case("true"), |_| true),
+        map(tag_no_case("false"), |_| false),
+    )))(input)
 }
 
 pub fn parse_i8(input: &str) -> IResult<&str, i8> {
-    map_res(
-        |input| {
-            let (input, num) = decimal(input)?;
-            let (input, _) = alt((char('b'), char('B')))(input)?;
-            Ok((input, num))
-        },
-        |s: &str| s.parse(),
-    )(input)
+    suffixed_int(input, Some('b'))
+}
+
+/// Like [`parse_i8`], but also accepts the unsigned bytes `128b` to `255b`,
+/// wrapping them to the byte with the same bits. So `200b` is `-56b`, as
+/// the game stores it.
+pub fn parse_wrapping_i8(input: &str) -> IResult<&str, i8> {
+    let (rest, n) = suffixed_int::<i16>(input, Some('b'))?;
+    match i8::try_from(n) {
+        Ok(n) => Ok((rest, n)),
+        Err(_) if (128..=255).contains(&n) => Ok((rest, n as u8 as i8)),
+        // Everything but the suffix, as `suffixed_int` would report it.
+        Err(_) => Err(nom::Err::Failure(nom::error::Error::new(
+            &input[..input.len() - rest.len() - 1],
+            ErrorKind::TooLarge,
+        ))),
+    }
 }
 
 pub fn parse_i16(input: &str) -> IResult<&str, i16> {
-    map_res(
-        |input| {
-            let (input, num) = decimal(input)?;
-            let (input, _) = alt((char('s'), char('S')))(input)?;
-            Ok((input, num))
-        },
-        |s: &str| s.parse(),
-    )(input)
+    suffixed_int(input, Some('s'))
+}
+
+/// Parse a decimal integer with the given type suffix, in either case, or no
+/// suffix for an int. It must be the whole token: if what follows would carry
+/// on an unquoted string, like the `x` of `12x`, this is a recoverable error
+/// so the token can be read as a string instead.
+///
+/// A whole token that does not fit the type is a [`Failure`](nom::Err::Failure) with
+/// [`ErrorKind::TooLarge`], holding the digits, rather than a recoverable error.
+/// Otherwise `300b` would go on to be read as an int followed by junk.
+fn suffixed_int<T: FromStr<Err = ParseIntError>>(
+    input: &str,
+    suffix: Option<char>,
+) -> IResult<&str, T> {
+    let (rest, num) = decimal(input)?;
+    let (rest, _) = match suffix {
+        Some(suffix) => alt((char(suffix), char(suffix.to_ascii_uppercase())))(rest)?,
+        None => (rest, ' '),
+    };
+    if continues_token(rest) {
+        return Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            ErrorKind::MapRes,
+        )));
+    }
+    match num.parse() {
+        Ok(n) => Ok((rest, n)),
+        Err(e)
+            if matches!(
+                e.kind(),
+                IntErrorKind::PosOverflow | IntErrorKind::NegOverflow
+            ) =>
+        {
+            Err(nom::Err::Failure(nom::error::Error::new(
+                num,
+                ErrorKind::TooLarge,
+            )))
+        }
+        Err(_) => Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            ErrorKind::MapRes,
+        ))),
+    }
 }
 
 pub fn parse_i32(input: &str) -> IResult<&str, i32> {
-    map_res(decimal, |s: &str| s.parse())(input)
+    suffixed_int(input, None)
 }
 
 pub fn parse_i64(input: &str) -> IResult<&str, i64> {
-    map_res(
-        |input| {
-            let (input, num) = decimal(input)?;
-            let (input, _) = alt((char('l'), char('L')))(input)?;
-            Ok((input, num))
-        },
-        |s: &str| s.parse(),
-    )(input)
+    suffixed_int(input, Some('l'))
+}
+
+/// Parse a decimal integer with no suffix as a long, as an element of a long
+/// array may be written even if it is too large for an int.
+pub fn parse_unsuffixed_i64(input: &str) -> IResult<&str, i64> {
+    suffixed_int(input, None)
+}
+
+/// Whether `rest` starts with a character that would carry on an unquoted
+/// string, so that what came before it is only the start of one.
+fn continues_token(rest: &str) -> bool {
+    rest.starts_with(|c: char| c.is_ascii_alphanumeric() || "_-.+".contains(c))
+}
+
+/// Apply `parser`, but fail recoverably if what it read is only the start of
+/// an unquoted string, like the `nan` of `nancy`. Integers check this
+/// themselves, before their range.
+fn whole_token<'a, T>(
+    mut parser: impl FnMut(&'a str) -> IResult<&'a str, T>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, T> {
+    move |input| match parser(input)? {
+        (rest, _) if continues_token(rest) => Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            ErrorKind::MapRes,
+        ))),
+        result => Ok(result),
+    }
+}
+
+pub fn parse_radix_i8(input: &str) -> IResult<&str, i8> {
+    radix_suffixed(input, Some('b'))
+}
+
+pub fn parse_radix_i16(input: &str) -> IResult<&str, i16> {
+    radix_suffixed(input, Some('s'))
+}
+
+pub fn parse_radix_i32(input: &str) -> IResult<&str, i32> {
+    radix_suffixed(input, None)
+}
+
+pub fn parse_radix_i64(input: &str) -> IResult<&str, i64> {
+    radix_suffixed(input, Some('l'))
+}
+
+/// Parse a `0x` hexadecimal or `0b` binary integer with an optional sign and
+/// the given type suffix, in either case. This is not vanilla sNBT. Hex digits
+/// are matched greedily, so a hex byte cannot be written as `b` is a digit.
+/// Like [`suffixed_int`], it must be the whole token, and one that does not fit
+/// the type is a [`Failure`](nom::Err::Failure) with [`ErrorKind::TooLarge`]
+/// rather than wrapping, so the decimal parsers do not go on to read the `0`.
+fn radix_suffixed<T: TryFrom<i128>>(input: &str, suffix: Option<char>) -> IResult<&str, T> {
+    let (rest, (sign, (radix, digits))) = pair(
+        opt(one_of("+-")),
+        alt((
+            pair(
+                map(tag_no_case("0x"), |_| 16),
+                take_while1(|c: char| c.is_ascii_hexdigit()),
+            ),
+            pair(map(tag_no_case("0b"), |_| 2), is_a("01")),
+        )),
+    )(input)?;
+    let literal = &input[..input.len() - rest.len()];
+    let (rest, _) = match suffix {
+        Some(suffix) => alt((char(suffix), char(suffix.to_ascii_uppercase())))(rest)?,
+        None => (rest, ' '),
+    };
+    if continues_token(rest) {
+        return Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            ErrorKind::MapRes,
+        )));
+    }
+    let n = i128::from_str_radix(digits, radix)
+        .ok()
+        .and_then(|n| T::try_from(if sign == Some('-') { -n } else { n }).ok());
+    match n {
+        Some(n) => Ok((rest, n)),
+        None => Err(nom::Err::Failure(nom::error::Error::new(
+            literal,
+            ErrorKind::TooLarge,
+        ))),
+    }
 }
 
 pub fn parse_f32(input: &str) -> IResult<&str, f32> {
-    map_res(
-        |input| {
-            let (input, num) = float(input)?;
-            let (input, _) = alt((char('f'), char('F')))(input)?;
-            Ok((input, num))
-        },
+    whole_token(map_res(
+        alt((
+            terminated(float, alt((char('f'), char('F')))),
+            terminated(float_int, alt((char('f'), char('F')))),
+        )),
         |s: &str| s.parse(),
-    )(input)
+    ))(input)
 }
 
+/// Parse a double. The `d` suffix is optional for numbers with a decimal point
+/// or exponent, but needed for ones that look like an int, eg `15d`.
 pub fn parse_f64(input: &str) -> IResult<&str, f64> {
-    map_res(
-        |input| {
-            let (input, num) = float(input)?;
-            let (input, _) = opt(alt((char('d'), char('D'))))(input)?;
-            Ok((input, num))
-        },
+    whole_token(map_res(
+        alt((
+            terminated(float, opt(alt((char('d'), char('D'))))),
+            terminated(float_int, alt((char('d'), char('D')))),
+        )),
         |s: &str| s.parse(),
-    )(input)
+    ))(input)
+}
+
+/// A float written like an int, which is only a float with a type suffix.
+/// Unlike ints, leading zeros are allowed.
+fn float_int(input: &str) -> IResult<&str, &str> {
+    recognize(pair(opt(one_of("+-")), digit1))(input)
 }
 
 fn float(input: &str) -> IResult<&str, &str> {
     alt((
-        tag_no_case("inf"),
+        // Longest match first, `inf` is a prefix of `infinity`.
         tag_no_case("infinity"),
+        tag_no_case("inf"),
         tag_no_case("nan"),
-        tag_no_case("-inf"),
         tag_no_case("-infinity"),
+        tag_no_case("-inf"),
         recognize(alt((
             map(
                 tuple((
@@ -1,11 +1,9 @@
-use std::io::Write;
-
-use byteorder::{BigEndian, ReadBytesExt};
-use serde::ser::{Impossible, SerializeSeq};
+use byteorder::{BigEndian, ByteOrder};
+use serde::ser::Impossible;
 
 use crate::{error::Error, error::Result};
 
-use super::Serializer;
+use super::{Serializer, Write};
 
 /// ArraySerializer is for serializing the NBT Arrays ie ByteArray, IntArray and
 /// LongArray.
@@ -52,24 +50,22 @@ impl<'a, W: Write> serde::Serializer for ArraySerializer<'a, W> {
     only_bytes!(serialize_unit_struct, &'static str);
 
     fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok> {
-        let mut serializer = super::ArraySerializer::new(self.prefix, self.ser)?;
         match self.stride {
             1 => {
                 let data = unsafe { &*(v as *const [u8] as *const [i8]) };
-                data.iter()
-                    .try_for_each(|i| SerializeSeq::serialize_element(&mut serializer, i))?
+                self.ser
+                    .write_int_array(self.prefix, data.iter().copied(), Some(b'b'))
+            }
+            4 => {
+                let data = v.chunks_exact(4).map(BigEndian::read_i32);
+                self.ser.write_int_array(self.prefix, data, None)
+            }
+            8 => {
+                let data = v.chunks_exact(8).map(BigEndian::read_i64);
+                self.ser.write_int_array(self.prefix, data, Some(b'l'))
             }
-            4 => v
-                .chunks_exact(4)
-                .map(|mut bs| bs.read_i32::<BigEndian>())
-                .try_for_each(|i| SerializeSeq::serialize_element(&mut serializer, &i?))?,
-            8 => v
-                .chunks_exact(8)
-                .map(|mut bs| bs.read_i64::<BigEndian>())
-                .try_for_each(|l| SerializeSeq::serialize_element(&mut serializer, &l?))?,
             _ => panic!(),
         }
-        SerializeSeq::end(serializer)
     }
 
     fn serialize_none(self) -> Result<Self::Ok> {
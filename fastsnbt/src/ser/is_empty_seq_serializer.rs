@@ -0,0 +1,171 @@
+use serde::{
+    ser::{Impossible, SerializeSeq, SerializeTuple},
+    Serializer,
+};
+
+use super::is_none_serializer::Mismatch;
+
+/// IsEmptySeqSerializer checks whether a value serializes as a list with no
+/// elements, without writing anything. Compounds use this to omit entries
+/// with an empty list value when [`SerOpts::skip_empty_seqs`] is set.
+///
+/// Like [`IsNoneSerializer`](super::is_none_serializer::IsNoneSerializer),
+/// anything else fails fast, with `false` or a `Mismatch`, both of which mean
+/// "not empty". A list stops at its first element.
+///
+/// [`SerOpts::skip_empty_seqs`]: crate::SerOpts::skip_empty_seqs
+pub(crate) struct IsEmptySeqSerializer;
+
+macro_rules! not_empty {
+    ($v:ident, $t:ty) => {
+        fn $v(self, _: $t) -> Result<bool, Mismatch> {
+            Ok(false)
+        }
+    };
+}
+
+/// A list being checked, which is empty if it ends without any elements.
+pub(crate) struct EmptySeq;
+
+impl SerializeSeq for EmptySeq {
+    type Ok = bool;
+    type Error = Mismatch;
+
+    fn serialize_element<T: ?Sized + serde::Serialize>(&mut self, _: &T) -> Result<(), Mismatch> {
+        Err(Mismatch)
+    }
+
+    fn end(self) -> Result<bool, Mismatch> {
+        Ok(true)
+    }
+}
+
+impl SerializeTuple for EmptySeq {
+    type Ok = bool;
+    type Error = Mismatch;
+
+    fn serialize_element<T: ?Sized + serde::Serialize>(&mut self, _: &T) -> Result<(), Mismatch> {
+        Err(Mismatch)
+    }
+
+    fn end(self) -> Result<bool, Mismatch> {
+        Ok(true)
+    }
+}
+
+impl Serializer for IsEmptySeqSerializer {
+    type Ok = bool;
+    type Error = Mismatch;
+    type SerializeSeq = EmptySeq;
+    type SerializeTuple = EmptySeq;
+    type SerializeTupleStruct = Impossible<bool, Mismatch>;
+    type SerializeTupleVariant = Impossible<bool, Mismatch>;
+    type SerializeMap = Impossible<bool, Mismatch>;
+    type SerializeStruct = Impossible<bool, Mismatch>;
+    type SerializeStructVariant = Impossible<bool, Mismatch>;
+
+    not_empty!(serialize_bool, bool);
+    not_empty!(serialize_i8, i8);
+    not_empty!(serialize_i16, i16);
+    not_empty!(serialize_i32, i32);
+    not_empty!(serialize_i64, i64);
+    not_empty!(serialize_i128, i128);
+    not_empty!(serialize_u8, u8);
+    not_empty!(serialize_u16, u16);
+    not_empty!(serialize_u32, u32);
+    not_empty!(serialize_u64, u64);
+    not_empty!(serialize_u128, u128);
+    not_empty!(serialize_f32, f32);
+    not_empty!(serialize_f64, f64);
+    not_empty!(serialize_char, char);
+    not_empty!(serialize_str, &str);
+    not_empty!(serialize_bytes, &[u8]);
+    not_empty!(serialize_unit_struct, &'static str);
+
+    fn serialize_none(self) -> Result<bool, Mismatch> {
+        Ok(false)
+    }
+
+    fn serialize_some<T: ?Sized + serde::Serialize>(self, value: &T) -> Result<bool, Mismatch> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<bool, Mismatch> {
+        Ok(false)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<bool, Mismatch> {
+        Ok(false)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + serde::Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<bool, Mismatch> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + serde::Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<bool, Mismatch> {
+        Ok(false)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Mismatch> {
+        Ok(EmptySeq)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Mismatch> {
+        Ok(EmptySeq)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Mismatch> {
+        Err(Mismatch)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Mismatch> {
+        Err(Mismatch)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Mismatch> {
+        Err(Mismatch)
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Mismatch> {
+        Err(Mismatch)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Mismatch> {
+        Err(Mismatch)
+    }
+}
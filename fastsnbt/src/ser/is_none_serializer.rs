@@ -0,0 +1,157 @@
+use core::fmt;
+
+use serde::{ser::Impossible, Serializer};
+
+/// IsNoneSerializer checks whether a value serializes as `None`, without
+/// writing anything. Compounds use this to omit entries with a `None` value
+/// before the key has been written.
+///
+/// Anything that is not `None` (or a `Some` or newtype wrapping `None`) fails
+/// fast, either with `false` or with a [`Mismatch`] for container types, which
+/// the caller should also treat as "not none".
+pub(crate) struct IsNoneSerializer;
+
+/// The error the checking serializers stop with once a value cannot match.
+/// It holds nothing, so that checking every compound entry does not allocate
+/// an error for each one that is written.
+#[derive(Debug)]
+pub(crate) struct Mismatch;
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("value does not match")
+    }
+}
+
+impl serde::ser::StdError for Mismatch {}
+
+impl serde::ser::Error for Mismatch {
+    fn custom<T: fmt::Display>(_msg: T) -> Self {
+        Mismatch
+    }
+}
+
+macro_rules! not_none {
+    ($v:ident, $t:ty) => {
+        fn $v(self, _: $t) -> Result<bool, Mismatch> {
+            Ok(false)
+        }
+    };
+}
+
+impl Serializer for IsNoneSerializer {
+    type Ok = bool;
+    type Error = Mismatch;
+    type SerializeSeq = Impossible<bool, Mismatch>;
+    type SerializeTuple = Impossible<bool, Mismatch>;
+    type SerializeTupleStruct = Impossible<bool, Mismatch>;
+    type SerializeTupleVariant = Impossible<bool, Mismatch>;
+    type SerializeMap = Impossible<bool, Mismatch>;
+    type SerializeStruct = Impossible<bool, Mismatch>;
+    type SerializeStructVariant = Impossible<bool, Mismatch>;
+
+    not_none!(serialize_bool, bool);
+    not_none!(serialize_i8, i8);
+    not_none!(serialize_i16, i16);
+    not_none!(serialize_i32, i32);
+    not_none!(serialize_i64, i64);
+    not_none!(serialize_i128, i128);
+    not_none!(serialize_u8, u8);
+    not_none!(serialize_u16, u16);
+    not_none!(serialize_u32, u32);
+    not_none!(serialize_u64, u64);
+    not_none!(serialize_u128, u128);
+    not_none!(serialize_f32, f32);
+    not_none!(serialize_f64, f64);
+    not_none!(serialize_char, char);
+    not_none!(serialize_str, &str);
+    not_none!(serialize_bytes, &[u8]);
+    not_none!(serialize_unit_struct, &'static str);
+
+    fn serialize_none(self) -> Result<bool, Mismatch> {
+        Ok(true)
+    }
+
+    fn serialize_some<T: ?Sized + serde::Serialize>(self, value: &T) -> Result<bool, Mismatch> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<bool, Mismatch> {
+        Ok(false)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<bool, Mismatch> {
+        Ok(false)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + serde::Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<bool, Mismatch> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + serde::Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<bool, Mismatch> {
+        Ok(false)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Mismatch> {
+        Err(Mismatch)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Mismatch> {
+        Err(Mismatch)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Mismatch> {
+        Err(Mismatch)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Mismatch> {
+        Err(Mismatch)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Mismatch> {
+        Err(Mismatch)
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Mismatch> {
+        Err(Mismatch)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Mismatch> {
+        Err(Mismatch)
+    }
+}
@@ -0,0 +1,58 @@
+use crate::error::Error;
+
+use super::Write;
+
+/// LimitWriter counts the bytes written to the inner writer, and fails any
+/// write that would take the total past the limit. Nothing of a failed write
+/// reaches the inner writer.
+pub(crate) struct LimitWriter<W> {
+    inner: W,
+    written: usize,
+    limit: Option<usize>,
+}
+
+impl<W> LimitWriter<W> {
+    pub(crate) fn new(inner: W, limit: Option<usize>) -> Self {
+        Self {
+            inner,
+            written: 0,
+            limit,
+        }
+    }
+
+    /// Create a writer for output that will later be copied into another
+    /// that has written `written` bytes, so that both share the one limit.
+    pub(crate) fn resume(inner: W, written: usize, limit: Option<usize>) -> Self {
+        Self {
+            inner,
+            written,
+            limit,
+        }
+    }
+
+    pub(crate) fn written(&self) -> usize {
+        self.written
+    }
+
+    pub(crate) fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for LimitWriter<W> {
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), Error> {
+        let written = self.written.saturating_add(buf.len());
+        match self.limit {
+            Some(limit) if written > limit => Err(Error::output_too_large(limit)),
+            _ => {
+                self.inner.write_all(buf)?;
+                self.written = written;
+                Ok(())
+            }
+        }
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        self.inner.flush()
+    }
+}
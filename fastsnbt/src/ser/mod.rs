@@ -8,62 +8,325 @@
 //!
 //! The [de](crate::de) module contains more information about (de)serialization.
 //!
+//! ## Strings
+//! Strings are always written quoted, so a string such as `"123"` or `"true"`
+//! is read back as a string rather than an int or a bool.
+//!
+//! Characters other than control characters, backslashes and the quote are
+//! written as they are, in UTF-8, including the replacement character U+FFFD.
+//! NBT strings are modified UTF-8 and may hold a lone surrogate, which no Rust
+//! string can. Reading such a string from binary NBT is already an error in
+//! `fastnbt`, and a string decoded lossily holds U+FFFD instead, so the output
+//! is always valid UTF-8.
+//!
+//! ## Options
+//! sNBT has no representation of `None`. A `None` entry in a compound, such
+//! as an `Option` struct field, is left out of the output entirely. A `None`
+//! anywhere else, such as at the top level, in a list, or in a newtype enum
+//! variant like `E::V(None)`, is an error.
+//!
+//! `Some` is written as the value inside it, so nested options collapse:
+//! `Some(Some(x))` is written as `x`, and `Some(None)` is treated like `None`.
+//! An `Option<Option<T>>` field therefore reads back as `None` rather than
+//! `Some(None)`. When deserializing, a value that is present is always `Some`.
+//!
+//! Vanilla also tends to leave out empty tags. With
+//! [`SerOpts::skip_empty_seqs`], an entry whose value is an empty list, such
+//! as an empty `Vec` field, is left out the same way as `None`.
+//!
+//! ## 128 bit integers
+//! NBT has no 128 bit integer type, so `i128` and `u128` are written as a
+//! quoted decimal string, eg `"340282366920938463463374607431768211455"`. These
+//! are read back as strings, so deserializing them into `i128` or `u128` needs
+//! something like `#[serde(with = ...)]` to parse the string.
+//!
+//! ## Arrays
+//! Serde has no notion of NBT's typed arrays, so they are written by
+//! serializing a map with a single entry. The key is one of
+//! [`BYTE_ARRAY_TOKEN`], [`INT_ARRAY_TOKEN`] or [`LONG_ARRAY_TOKEN`], and the
+//! value is the elements as big-endian bytes, serialized with
+//! `serialize_bytes`. The map is written as the array rather than as a
+//! compound. This is the same scheme `fastnbt` uses,
+//! so [`ByteArray`](fastnbt::ByteArray), [`IntArray`](fastnbt::IntArray) and
+//! [`LongArray`](fastnbt::LongArray) work with both crates and are usually
+//! what you want. Using the tokens directly looks like this:
+//!
+//! ```
+//! use std::collections::HashMap;
+//! use serde_bytes::Bytes;
+//!
+//! let bytes: Vec<u8> = [1i32, -2, 3].iter().flat_map(|i| i.to_be_bytes()).collect();
+//! let array = HashMap::from([(fastsnbt::INT_ARRAY_TOKEN, Bytes::new(&bytes))]);
+//!
+//! let data = HashMap::from([("ints", array)]);
+//! assert_eq!("{\"ints\":[I;1,-2,3]}", fastsnbt::to_string(&data).unwrap());
+//! ```
+//!
+//! A value that is not bytes is an error. Trailing bytes that do not make up a
+//! whole int or long are ignored.
+//!
 //! ## Uuid
 //! Because sNBT is a human-readable format,
 //! `Uuid`s are represented as strings.
 
-use std::io::Write;
+use alloc::{format, string::ToString, vec::Vec};
 
 use serde::ser::{
-    self, Impossible, SerializeMap, SerializeSeq, SerializeStruct, SerializeTuple,
+    self, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
     SerializeTupleStruct, SerializeTupleVariant,
 };
 
-use crate::{error::Error, BYTE_ARRAY_TOKEN_STR, INT_ARRAY_TOKEN_STR, LONG_ARRAY_TOKEN_STR};
+use crate::{
+    error::Error, parser::is_simple_string, Dialect, Indent, Quoting, SerOpts, BYTE_ARRAY_TOKEN,
+    INT_ARRAY_TOKEN, LONG_ARRAY_TOKEN,
+};
 
-use self::name_serializer::NameSerializer;
+use self::{
+    is_empty_seq_serializer::IsEmptySeqSerializer, is_none_serializer::IsNoneSerializer,
+    limit_writer::LimitWriter, name_serializer::NameSerializer,
+};
 
 mod array_serializer;
+mod is_empty_seq_serializer;
+mod is_none_serializer;
+mod limit_writer;
 mod name_serializer;
+#[cfg(feature = "std")]
+mod value_writer;
+
+/// The newtype struct name a [`LosslessValue::Raw`](crate::LosslessValue::Raw)
+/// serializes its value as, with its text in [`RAW_TEXT`].
+#[cfg(feature = "lossless")]
+pub(crate) const RAW_VALUE_TOKEN: &str = "__fastnbt_raw_value";
+
+#[cfg(feature = "lossless")]
+std::thread_local! {
+    /// The text of the raw number being serialized, which the serializer
+    /// writes in place of its value.
+    pub(crate) static RAW_TEXT: core::cell::Cell<Option<String>> = const { core::cell::Cell::new(None) };
+}
+
+/// Where a [`Serializer`] writes its output. With the `std` feature, which is
+/// on by default, this is implemented for every `std::io::Write`. Without it,
+/// it is implemented for `Vec<u8>` and `&mut W`, and can be implemented for
+/// anything else bytes can go, such as a fixed buffer. An error for a write
+/// that fails can be made with [`serde::ser::Error::custom`].
+/// [`to_fmt_writer`](crate::to_fmt_writer) writes into a `core::fmt::Write`
+/// either way.
+///
+/// With `std`, a `&mut W` is only a `Write` when `W` is a `std::io::Write`, so
+/// pass a writer of your own by value and take it back with
+/// [`Serializer::into_inner`].
+pub trait Write {
+    /// Write all of `buf`, or fail.
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), Error>;
+
+    /// Flush anything buffered through to where it is going.
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: ?Sized + std::io::Write> Write for W {
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), Error> {
+        Ok(std::io::Write::write_all(self, buf)?)
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(std::io::Write::flush(self)?)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl Write for Vec<u8> {
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), Error> {
+        self.extend_from_slice(buf);
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<W: ?Sized + Write> Write for &mut W {
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), Error> {
+        (**self).write_all(buf)
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        (**self).flush()
+    }
+}
 
-pub(crate) fn write_escaped_str<W: Write>(mut writer: W, v: &str) -> Result<(), Error> {
-    writer.write_all(b"\"")?;
+/// Write `v` as a quoted sNBT string. Backslashes, the quote and control
+/// characters are escaped, the latter as `\uXXXX` when there is no shorter
+/// escape. Everything else is written as is. Bedrock only has escapes for
+/// backslashes and quotes, so control characters are written as is too.
+pub(crate) fn write_escaped_str<W: ?Sized + Write>(
+    writer: &mut W,
+    v: &str,
+    quoting: Quoting,
+    dialect: Dialect,
+) -> Result<(), Error> {
+    let quote = match quoting {
+        Quoting::Double => b'"',
+        Quoting::Single => b'\'',
+        Quoting::Minimal => {
+            let doubles = v.bytes().filter(|&b| b == b'"').count();
+            let singles = v.bytes().filter(|&b| b == b'\'').count();
+            if singles < doubles {
+                b'\''
+            } else {
+                b'"'
+            }
+        }
+    };
+    writer.write_all(&[quote])?;
     let bytes = v.as_bytes();
     let mut start = 0;
     for (i, &byte) in bytes.iter().enumerate() {
-        if byte != b'"' && byte != b'\\' {
-            continue;
-        }
+        let escape = match byte {
+            b'\\' => b'\\',
+            _ if byte == quote => quote,
+            _ if dialect == Dialect::Bedrock => continue,
+            b'\n' => b'n',
+            b'\t' => b't',
+            b'\r' => b'r',
+            0x08 => b'b',
+            0x0c => b'f',
+            0..=0x1f => b'u',
+            _ => continue,
+        };
         if start < i {
             writer.write_all(v[start..i].as_bytes())?;
         }
-        if byte == b'"' {
-            writer.write_all(b"\\\"")?;
-        } else if byte == b'\\' {
-            writer.write_all(b"\\\\")?;
+        writer.write_all(&[b'\\', escape])?;
+        if escape == b'u' {
+            const HEX: &[u8; 16] = b"0123456789abcdef";
+            writer.write_all(&[
+                b'0',
+                b'0',
+                HEX[(byte >> 4) as usize],
+                HEX[(byte & 0xf) as usize],
+            ])?;
         }
         start = i + 1;
     }
     if start != bytes.len() {
         writer.write_all(v[start..].as_bytes())?;
     }
-    Ok(writer.write_all(b"\"")?)
+    writer.write_all(&[quote])
+}
+
+/// Write `key` as a compound key, left unquoted if that is allowed and it
+/// reads back the same.
+pub(crate) fn write_key<W: ?Sized + Write>(
+    writer: &mut W,
+    key: &str,
+    quoting: Quoting,
+    unquoted: bool,
+    dialect: Dialect,
+) -> Result<(), Error> {
+    if unquoted && is_simple_string(key) {
+        writer.write_all(key.as_bytes())
+    } else {
+        write_escaped_str(writer, key, quoting, dialect)
+    }
+}
+
+/// How many bytes of formatted array elements are buffered before being
+/// written out.
+const ARRAY_CHUNK_LEN: usize = 8 * 1024;
+
+/// Write a newline followed by `indent` levels of indentation, or nothing if
+/// the output is not indented.
+fn write_newline<W: ?Sized + Write>(
+    writer: &mut W,
+    indent: Option<usize>,
+    unit: Indent,
+) -> Result<(), Error> {
+    if let Some(indent) = indent {
+        writer.write_all(b"\n")?;
+        let (unit, width): (&[u8], usize) = match unit {
+            Indent::Spaces(width) => (b" ", width),
+            Indent::Tabs => (b"\t", 1),
+        };
+        for _ in 0..indent * width {
+            writer.write_all(unit)?;
+        }
+    }
+    Ok(())
 }
 
+/// Whether a compound entry with this value is left out: always for `None`,
+/// and for an empty list if the options say so. Neither check allocates an
+/// error, and the second is only made when skipping empty lists.
+fn is_omitted<T: ?Sized + ser::Serialize>(value: &T, opts: &SerOpts) -> bool {
+    value.serialize(IsNoneSerializer).unwrap_or(false)
+        || (opts.skip_empty_seqs && value.serialize(IsEmptySeqSerializer).unwrap_or(false))
+}
+
+/// The token for a float that is not finite, without a type suffix.
+fn non_finite(nan: bool, negative: bool) -> &'static [u8] {
+    match (nan, negative) {
+        (true, _) => b"NaN",
+        (false, false) => b"Infinity",
+        (false, true) => b"-Infinity",
+    }
+}
+
+/// Serializer for sNBT. Most of the time [`to_string`](crate::to_string) and
+/// friends are more convenient, but this can be used to serialize into any
+/// [`Write`], or to serialize several values one after another.
+///
+/// ```
+/// # use serde::Serialize;
+/// use fastsnbt::{ser::Serializer, SerOpts};
+///
+/// let mut out = Vec::new();
+/// let mut serializer = Serializer::with_opts(&mut out, SerOpts::new().sort_keys(true));
+/// 5i32.serialize(&mut serializer).unwrap();
+/// "text".serialize(&mut serializer).unwrap();
+/// assert_eq!(b"5\"text\"", out.as_slice());
+/// ```
 pub struct Serializer<W> {
-    pub(crate) writer: W,
+    pub(crate) writer: LimitWriter<W>,
     pub(crate) indent: Option<usize>,
+    pub(crate) opts: SerOpts,
 }
 
 impl<W: Write> Serializer<W> {
-    pub fn newline(&mut self) -> Result<(), Error> {
-        if let Some(indent) = self.indent {
-            self.writer.write_all(b"\n")?;
-            for _ in 0..indent {
-                self.writer.write_all(b"    ")?;
-            }
+    /// Create a serializer producing compact sNBT.
+    pub fn new(writer: W) -> Self {
+        Self::with_opts(writer, SerOpts::new())
+    }
+
+    /// Create a serializer producing pretty printed sNBT.
+    pub fn pretty(writer: W) -> Self {
+        Self::with_opts(writer, SerOpts::pretty())
+    }
+
+    /// Create a serializer with the given options.
+    pub fn with_opts(writer: W, opts: SerOpts) -> Self {
+        Self {
+            writer: LimitWriter::new(writer, opts.max_output_bytes),
+            indent: if opts.is_pretty() { Some(0) } else { None },
+            opts,
         }
-        Ok(())
+    }
+
+    /// Flush the underlying writer, such as a [`BufWriter`](std::io::BufWriter),
+    /// so that everything serialized so far has been written through it.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.writer.flush()
+    }
+
+    /// Unwrap the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner()
+    }
+
+    pub fn newline(&mut self) -> Result<(), Error> {
+        write_newline(&mut self.writer, self.indent, self.opts.indent)
     }
 
     pub fn push_indent(&mut self) {
@@ -73,6 +336,92 @@ impl<W: Write> Serializer<W> {
     pub fn pop_indent(&mut self) {
         self.indent = self.indent.map(|indent| indent.saturating_sub(1));
     }
+
+    pub(crate) fn write_separator(&mut self) -> Result<(), Error> {
+        let sep: &[u8] = if self.opts.is_pretty() { b": " } else { b":" };
+        self.writer.write_all(sep)
+    }
+
+    /// Write the type suffix of a number, in the case chosen by the options.
+    fn write_suffix(&mut self, suffix: u8) -> Result<(), Error> {
+        self.writer.write_all(&[self.suffix(suffix)])
+    }
+
+    /// The type suffix of a number, in the case chosen by the options.
+    fn suffix(&self, suffix: u8) -> u8 {
+        if self.opts.uppercase_suffixes {
+            suffix.to_ascii_uppercase()
+        } else {
+            suffix
+        }
+    }
+
+    /// Write a list or NBT array of integers straight to the writer, without
+    /// going through serde for every element. The output is the same as
+    /// serializing each element with an [`ArraySerializer`]. Elements are
+    /// formatted into a buffer that is written out a chunk at a time, rather
+    /// than with several small writes each, which adds up for the huge arrays
+    /// of chunk data.
+    pub(crate) fn write_int_array<I: itoa::Integer>(
+        &mut self,
+        prefix: &str,
+        values: impl IntoIterator<Item = I>,
+        suffix: Option<u8>,
+    ) -> Result<(), Error> {
+        let mut values = values.into_iter().peekable();
+        self.writer.write_all(b"[")?;
+        if values.peek().is_none() {
+            self.writer.write_all(prefix.as_bytes())?;
+            return self.writer.write_all(b"]");
+        }
+
+        self.push_indent();
+        if !prefix.is_empty() {
+            self.newline()?;
+            self.writer.write_all(prefix.as_bytes())?;
+        }
+        let suffix = suffix.map(|suffix| self.suffix(suffix));
+        let mut chunk = Vec::with_capacity(ARRAY_CHUNK_LEN + 64);
+        let mut buffer = itoa::Buffer::new();
+        for (i, v) in values.enumerate() {
+            if i > 0 {
+                chunk.push(b',');
+            }
+            write_newline(&mut chunk, self.indent, self.opts.indent)?;
+            chunk.extend_from_slice(buffer.format(v).as_bytes());
+            chunk.extend(suffix);
+            if chunk.len() >= ARRAY_CHUNK_LEN {
+                self.writer.write_all(&chunk)?;
+                chunk.clear();
+            }
+        }
+        self.writer.write_all(&chunk)?;
+        self.pop_indent();
+        self.newline()?;
+        self.writer.write_all(b"]")
+    }
+
+    /// Enum variants with data are externally tagged, like serde_json, as a
+    /// compound with the variant name as the single key. This opens the
+    /// compound and writes the key, ready for the value to be written.
+    pub(crate) fn begin_variant(&mut self, variant: &str) -> Result<(), Error> {
+        self.writer.write_all(b"{")?;
+        self.push_indent();
+        self.newline()?;
+        write_escaped_str(
+            &mut self.writer,
+            variant,
+            self.opts.quoting,
+            self.opts.dialect,
+        )?;
+        self.write_separator()
+    }
+
+    pub(crate) fn end_variant(&mut self) -> Result<(), Error> {
+        self.pop_indent();
+        self.newline()?;
+        self.writer.write_all(b"}")
+    }
 }
 
 impl<'a, W: 'a + Write> ser::Serializer for &'a mut Serializer<W> {
@@ -84,77 +433,117 @@ impl<'a, W: 'a + Write> ser::Serializer for &'a mut Serializer<W> {
     type SerializeTupleVariant = ArraySerializer<'a, W>;
     type SerializeMap = CompoundSerializer<'a, W>;
     type SerializeStruct = CompoundSerializer<'a, W>;
-    type SerializeStructVariant = Impossible<(), Error>;
+    type SerializeStructVariant = CompoundSerializer<'a, W>;
 
     fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
-        Ok(self.writer.write_all(if v { b"true" } else { b"false" })?)
+        if self.opts.bools_as_bytes {
+            return self.serialize_i8(v as i8);
+        }
+        self.writer.write_all(if v { b"true" } else { b"false" })
     }
 
     fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
         let mut buffer = itoa::Buffer::new();
         let s = buffer.format(v);
         self.writer.write_all(s.as_bytes())?;
-        Ok(self.writer.write_all(b"b")?)
+        self.write_suffix(b'b')
     }
 
     fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
         let mut buffer = itoa::Buffer::new();
         let s = buffer.format(v);
         self.writer.write_all(s.as_bytes())?;
-        Ok(self.writer.write_all(b"s")?)
+        self.write_suffix(b's')
     }
 
     fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
         let mut buffer = itoa::Buffer::new();
         let s = buffer.format(v);
-        Ok(self.writer.write_all(s.as_bytes())?)
+        self.writer.write_all(s.as_bytes())
     }
 
     fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
         let mut buffer = itoa::Buffer::new();
         let s = buffer.format(v);
         self.writer.write_all(s.as_bytes())?;
-        Ok(self.writer.write_all(b"l")?)
+        self.write_suffix(b'l')
     }
 
     fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
         let mut buffer = itoa::Buffer::new();
         let s = buffer.format(v);
         self.writer.write_all(s.as_bytes())?;
-        Ok(self.writer.write_all(b"b")?)
+        self.write_suffix(b'b')
     }
 
     fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
         let mut buffer = itoa::Buffer::new();
         let s = buffer.format(v);
         self.writer.write_all(s.as_bytes())?;
-        Ok(self.writer.write_all(b"s")?)
+        self.write_suffix(b's')
     }
 
     fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
         let mut buffer = itoa::Buffer::new();
         let s = buffer.format(v);
-        Ok(self.writer.write_all(s.as_bytes())?)
+        self.writer.write_all(s.as_bytes())
     }
 
     fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
         let mut buffer = itoa::Buffer::new();
         let s = buffer.format(v);
         self.writer.write_all(s.as_bytes())?;
-        Ok(self.writer.write_all(b"l")?)
+        self.write_suffix(b'l')
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
+        // NBT has no 128 bit integers, but a decimal string is lossless.
+        let mut buffer = itoa::Buffer::new();
+        self.serialize_str(buffer.format(v))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
+        let mut buffer = itoa::Buffer::new();
+        self.serialize_str(buffer.format(v))
     }
 
     fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
+        if !v.is_finite() {
+            self.writer
+                .write_all(non_finite(v.is_nan(), v.is_sign_negative()))?;
+            return self.write_suffix(b'f');
+        }
+        if let Some(precision) = self.opts.float_precision {
+            self.writer
+                .write_all(format!("{v:.precision$}").as_bytes())?;
+            return self.write_suffix(b'f');
+        }
         let mut buffer = ryu::Buffer::new();
-        let s = buffer.format(v);
+        let s = buffer.format_finite(v);
         self.writer.write_all(s.as_bytes())?;
-        Ok(self.writer.write_all(b"f")?)
+        self.write_suffix(b'f')
     }
 
     fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
+        if !v.is_finite() {
+            // Unlike finite doubles these get a suffix, `NaN` reads a lot like
+            // a string otherwise.
+            self.writer
+                .write_all(non_finite(v.is_nan(), v.is_sign_negative()))?;
+            return self.write_suffix(b'd');
+        }
+        if let Some(precision) = self.opts.float_precision {
+            self.writer
+                .write_all(format!("{v:.precision$}").as_bytes())?;
+            // Without a decimal point this would be read back as an int.
+            if precision == 0 {
+                self.write_suffix(b'd')?;
+            }
+            return Ok(());
+        }
         let mut buffer = ryu::Buffer::new();
-        let s = buffer.format(v);
-        Ok(self.writer.write_all(s.as_bytes())?)
+        let s = buffer.format_finite(v);
+        self.writer.write_all(s.as_bytes())
     }
 
     fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
@@ -163,19 +552,18 @@ impl<'a, W: 'a + Write> ser::Serializer for &'a mut Serializer<W> {
     }
 
     fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
-        write_escaped_str(&mut self.writer, v)
+        write_escaped_str(&mut self.writer, v, self.opts.quoting, self.opts.dialect)
     }
 
     fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
-        let mut serializer = ArraySerializer::new("", self)?;
-        for byte in v {
-            SerializeSeq::serialize_element(&mut serializer, byte)?;
-        }
-        SerializeSeq::end(serializer)
+        self.write_int_array("", v.iter().copied(), Some(b'b'))
     }
 
     fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
-        Err(Error::bespoke("cannot serialize None".to_string()))
+        Err(Error::unsupported_type(
+            "None",
+            "cannot serialize None".to_string(),
+        ))
     }
 
     fn serialize_some<T: ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error>
@@ -186,13 +574,17 @@ impl<'a, W: 'a + Write> ser::Serializer for &'a mut Serializer<W> {
     }
 
     fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
-        Err(Error::bespoke("cannot serialize unit: ()".to_string()))
+        Err(Error::unsupported_type(
+            "()",
+            "cannot serialize unit: ()".to_string(),
+        ))
     }
 
     fn serialize_unit_struct(self, name: &'static str) -> Result<Self::Ok, Self::Error> {
-        Err(Error::bespoke(format!(
-            "cannot serialize unit struct: {name}"
-        )))
+        Err(Error::unsupported_type(
+            "unit struct",
+            format!("cannot serialize unit struct: {name}"),
+        ))
     }
 
     fn serialize_unit_variant(
@@ -204,14 +596,21 @@ impl<'a, W: 'a + Write> ser::Serializer for &'a mut Serializer<W> {
         self.serialize_str(variant)
     }
 
+    #[cfg_attr(not(feature = "lossless"), allow(unused_variables))]
     fn serialize_newtype_struct<T: ?Sized>(
         self,
-        _name: &'static str,
+        name: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
         T: serde::Serialize,
     {
+        #[cfg(feature = "lossless")]
+        if name == RAW_VALUE_TOKEN {
+            if let Some(text) = RAW_TEXT.take() {
+                return self.writer.write_all(text.as_bytes());
+            }
+        }
         value.serialize(self)
     }
 
@@ -219,15 +618,32 @@ impl<'a, W: 'a + Write> ser::Serializer for &'a mut Serializer<W> {
         self,
         _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
-        _value: &T,
+        variant: &'static str,
+        value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
         T: serde::Serialize,
     {
-        Err(Error::bespoke(
-            "cannot serialize newtype variant, please open fastnbt issue".to_string(),
-        ))
+        // Leaving the entry out like other `None`s would lose the variant, so
+        // this is an error like a `None` on its own.
+        if value.serialize(IsNoneSerializer).unwrap_or(false) {
+            return self.serialize_none();
+        }
+        // The entry is written even for an empty list, which
+        // `skip_empty_seqs` only leaves out of real compounds.
+        self.writer.write_all(b"{")?;
+        self.push_indent();
+        self.newline()?;
+        write_key(
+            &mut self.writer,
+            variant,
+            self.opts.quoting,
+            self.opts.unquoted_keys,
+            self.opts.dialect,
+        )?;
+        self.write_separator()?;
+        value.serialize(&mut *self)?;
+        self.end_variant()
     }
 
     fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
@@ -250,12 +666,13 @@ impl<'a, W: 'a + Write> ser::Serializer for &'a mut Serializer<W> {
         self,
         _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleVariant, Self::Error> {
-        Err(Error::bespoke(
-            "cannot serialize newtype tuple variant, please open fastnbt issue".to_string(),
-        ))
+        self.begin_variant(variant)?;
+        let mut array = ArraySerializer::new("", self)?;
+        array.variant = true;
+        Ok(array)
     }
 
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
@@ -274,19 +691,25 @@ impl<'a, W: 'a + Write> ser::Serializer for &'a mut Serializer<W> {
         self,
         _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStructVariant, Self::Error> {
-        Err(Error::bespoke(
-            "cannot serialize struct variant, please open fastnbt issue".to_string(),
-        ))
+        self.begin_variant(variant)?;
+        let mut compound = CompoundSerializer::new(self)?;
+        compound.variant = true;
+        Ok(compound)
     }
 }
 
 pub struct ArraySerializer<'a, W> {
-    first: bool,
+    /// Whether the first element has been written. The opening bracket and
+    /// prefix are only written along with it, so `end` writes all of `[]` if
+    /// there were no elements.
+    wrote_first: bool,
     serializer: &'a mut Serializer<W>,
     prefix: &'static str,
+    /// Whether this is the content of an enum variant, which needs closing.
+    variant: bool,
 }
 
 impl<'a, W: Write> ArraySerializer<'a, W> {
@@ -295,9 +718,10 @@ impl<'a, W: Write> ArraySerializer<'a, W> {
         serializer: &'a mut Serializer<W>,
     ) -> Result<ArraySerializer<'a, W>, Error> {
         Ok(Self {
-            first: false,
+            wrote_first: false,
             serializer,
             prefix,
+            variant: false,
         })
     }
 }
@@ -310,8 +734,8 @@ impl<'a, W: Write> SerializeSeq for ArraySerializer<'a, W> {
     where
         T: serde::Serialize,
     {
-        if !self.first {
-            self.first = true;
+        if !self.wrote_first {
+            self.wrote_first = true;
 
             self.serializer.writer.write_all(b"[")?;
             self.serializer.push_indent();
@@ -327,14 +751,18 @@ impl<'a, W: Write> SerializeSeq for ArraySerializer<'a, W> {
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        if self.first {
+        if self.wrote_first {
             self.serializer.pop_indent();
             self.serializer.newline()?;
         } else {
             self.serializer.writer.write_all(b"[")?;
             self.serializer.writer.write_all(self.prefix.as_bytes())?;
         }
-        Ok(self.serializer.writer.write_all(b"]")?)
+        self.serializer.writer.write_all(b"]")?;
+        if self.variant {
+            self.serializer.end_variant()?;
+        }
+        Ok(())
     }
 }
 
@@ -386,22 +814,122 @@ impl<'a, W: Write + 'a> SerializeTupleVariant for ArraySerializer<'a, W> {
     }
 }
 
+/// Writes a compound one field at a time, for when the fields are not all
+/// known up front, as returned by [`compound_writer`](crate::compound_writer).
+/// Each field is written out as soon as it is given, so
+/// [`SerOpts::sort_keys`] has no effect. The compound is only complete once
+/// [`finish`](Self::finish) is called.
+///
+/// ```
+/// # use fastsnbt::SerOpts;
+/// let mut out = Vec::new();
+/// let opts = SerOpts::new().unquoted_keys(true);
+/// let mut compound = fastsnbt::compound_writer_with_opts(&mut out, opts).unwrap();
+/// compound.field("x", &1).unwrap();
+/// compound.field("y", &[1.5f32, 2.0]).unwrap();
+/// compound.finish().unwrap();
+/// assert_eq!(b"{x:1,y:[1.5f,2.0f]}", out.as_slice());
+/// ```
+pub struct CompoundWriter<W> {
+    serializer: Serializer<W>,
+    has_first: bool,
+}
+
+impl<W: Write> CompoundWriter<W> {
+    /// Start a compound, writing its opening brace.
+    pub(crate) fn new(mut serializer: Serializer<W>) -> Result<Self, Error> {
+        serializer.writer.write_all(b"{")?;
+        serializer.push_indent();
+        Ok(Self {
+            serializer,
+            has_first: false,
+        })
+    }
+
+    /// Write a field of the compound. Like fields of a struct, a value of
+    /// `None` is left out, as is an empty list if
+    /// [`SerOpts::skip_empty_seqs`] is set.
+    pub fn field<T: ?Sized + ser::Serialize>(
+        &mut self,
+        name: &str,
+        value: &T,
+    ) -> Result<(), Error> {
+        if is_omitted(value, &self.serializer.opts) {
+            return Ok(());
+        }
+        if self.has_first {
+            self.serializer.writer.write_all(b",")?;
+        }
+        self.has_first = true;
+        self.serializer.newline()?;
+        let opts = &self.serializer.opts;
+        write_key(
+            &mut self.serializer.writer,
+            name,
+            opts.quoting,
+            opts.unquoted_keys,
+            opts.dialect,
+        )?;
+        self.serializer.write_separator()?;
+        value.serialize(&mut self.serializer)
+    }
+
+    /// Flush the underlying writer, so the fields written so far reach it.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.serializer.flush()
+    }
+
+    /// End the compound, writing its closing brace, and return the writer.
+    pub fn finish(mut self) -> Result<W, Error> {
+        self.serializer.pop_indent();
+        if self.has_first {
+            self.serializer.newline()?;
+        }
+        self.serializer.writer.write_all(b"}")?;
+        if self.serializer.opts.trailing_newline {
+            self.serializer.writer.write_all(b"\n")?;
+        }
+        if self.serializer.opts.auto_flush {
+            self.serializer.flush()?;
+        }
+        Ok(self.serializer.into_inner())
+    }
+}
+
 pub struct CompoundSerializer<'a, W> {
     serializer: &'a mut Serializer<W>,
     is_compound: bool,
     has_first: bool,
     key: Option<Vec<u8>>,
+    /// Serialized name and value of each entry, if the keys are to be sorted
+    /// before being written.
+    sorted: Option<Vec<(Vec<u8>, Vec<u8>)>>,
+    /// How many bytes of entries are in `sorted`. These count towards the
+    /// output limit before they are written.
+    buffered: usize,
+    /// Whether this is the content of an enum variant, which needs closing.
+    variant: bool,
 }
 
 impl<'a, W: Write + 'a> CompoundSerializer<'a, W> {
     pub fn new(serializer: &'a mut Serializer<W>) -> Result<CompoundSerializer<'a, W>, Error> {
+        let sorted = serializer.opts.sort_keys.then(Vec::new);
         Ok(Self {
             serializer,
             is_compound: false,
             has_first: false,
             key: None,
+            sorted,
+            buffered: 0,
+            variant: false,
         })
     }
+
+    fn write_entry(&mut self, name: &[u8], value: &[u8]) -> Result<(), Error> {
+        self.serializer.writer.write_all(name)?;
+        self.serializer.write_separator()?;
+        self.serializer.writer.write_all(value)
+    }
 }
 
 impl<'a, W: Write + 'a> SerializeMap for CompoundSerializer<'a, W> {
@@ -413,7 +941,12 @@ impl<'a, W: Write + 'a> SerializeMap for CompoundSerializer<'a, W> {
         T: serde::Serialize,
     {
         let mut name = Vec::new();
-        key.serialize(&mut NameSerializer { name: &mut name })?;
+        key.serialize(&mut NameSerializer {
+            name: &mut name,
+            quoting: self.serializer.opts.quoting,
+            unquoted_keys: self.serializer.opts.unquoted_keys,
+            dialect: self.serializer.opts.dialect,
+        })?;
         self.key = Some(name);
         Ok(())
     }
@@ -426,25 +959,39 @@ impl<'a, W: Write + 'a> SerializeMap for CompoundSerializer<'a, W> {
             Error::bespoke("serialize_value called before serialize_key".to_string())
         })?;
 
+        // There is no sNBT for None, so leave the entry out entirely.
+        if is_omitted(value, &self.serializer.opts) {
+            return Ok(());
+        }
+
         if !self.has_first {
             self.has_first = true;
-        } else {
+        } else if self.sorted.is_none() {
             self.serializer.writer.write_all(b",")?;
             self.serializer.newline()?;
         }
 
-        match std::str::from_utf8(&name) {
-            Ok(BYTE_ARRAY_TOKEN_STR) => value.serialize(array_serializer::ArraySerializer {
+        // The array tokens need no escaping, so are the same between the
+        // quotes whichever quote character is in use, and are left bare if
+        // keys are unquoted.
+        let unquoted = match name.first() {
+            Some(b'"' | b'\'') => name
+                .get(1..name.len().saturating_sub(1))
+                .unwrap_or_default(),
+            _ => &name[..],
+        };
+        match core::str::from_utf8(unquoted) {
+            Ok(BYTE_ARRAY_TOKEN) => value.serialize(array_serializer::ArraySerializer {
                 ser: self.serializer,
                 stride: 1,
                 prefix: "B;",
             }),
-            Ok(INT_ARRAY_TOKEN_STR) => value.serialize(array_serializer::ArraySerializer {
+            Ok(INT_ARRAY_TOKEN) => value.serialize(array_serializer::ArraySerializer {
                 ser: self.serializer,
                 stride: 4,
                 prefix: "I;",
             }),
-            Ok(LONG_ARRAY_TOKEN_STR) => value.serialize(array_serializer::ArraySerializer {
+            Ok(LONG_ARRAY_TOKEN) => value.serialize(array_serializer::ArraySerializer {
                 ser: self.serializer,
                 stride: 8,
                 prefix: "L;",
@@ -454,21 +1001,48 @@ impl<'a, W: Write + 'a> SerializeMap for CompoundSerializer<'a, W> {
                     self.is_compound = true;
                     self.serializer.writer.write_all(b"{")?;
                     self.serializer.push_indent();
-                    self.serializer.newline()?;
+                    if self.sorted.is_none() {
+                        self.serializer.newline()?;
+                    }
                 }
-                self.serializer.writer.write_all(&name)?;
-                let sep: &[u8] = if self.serializer.indent.is_some() {
-                    b": "
+                if let Some(sorted) = &mut self.sorted {
+                    // Buffered entries share what is left of the limit, so
+                    // that together they cannot go over it.
+                    let written = self.serializer.writer.written() + self.buffered + name.len();
+                    let mut entry = Serializer {
+                        writer: LimitWriter::resume(
+                            Vec::new(),
+                            written,
+                            self.serializer.opts.max_output_bytes,
+                        ),
+                        indent: self.serializer.indent,
+                        opts: self.serializer.opts.clone(),
+                    };
+                    value.serialize(&mut entry)?;
+                    let value = entry.into_inner();
+                    self.buffered += name.len() + value.len();
+                    sorted.push((name, value));
+                    Ok(())
                 } else {
-                    b":"
-                };
-                self.serializer.writer.write_all(sep)?;
-                value.serialize(&mut *self.serializer)
+                    self.serializer.writer.write_all(&name)?;
+                    self.serializer.write_separator()?;
+                    value.serialize(&mut *self.serializer)
+                }
             }
         }
     }
 
-    fn end(self) -> Result<Self::Ok, Self::Error> {
+    fn end(mut self) -> Result<Self::Ok, Self::Error> {
+        if let Some(mut sorted) = self.sorted.take() {
+            sorted.sort_by(|(a, _), (b, _)| a.cmp(b));
+            for (i, (name, value)) in sorted.iter().enumerate() {
+                if i != 0 {
+                    self.serializer.writer.write_all(b",")?;
+                }
+                self.serializer.newline()?;
+                self.write_entry(name, value)?;
+            }
+        }
         if self.is_compound {
             self.serializer.pop_indent();
             self.serializer.newline()?;
@@ -476,6 +1050,9 @@ impl<'a, W: Write + 'a> SerializeMap for CompoundSerializer<'a, W> {
         } else if !self.has_first {
             self.serializer.writer.write_all(b"{}")?;
         }
+        if self.variant {
+            self.serializer.end_variant()?;
+        }
         Ok(())
     }
 }
@@ -499,3 +1076,20 @@ impl<'a, W: Write + 'a> SerializeStruct for CompoundSerializer<'a, W> {
         SerializeMap::end(self)
     }
 }
+
+impl<'a, W: Write + 'a> SerializeStructVariant for CompoundSerializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + serde::Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        SerializeMap::serialize_entry(self, key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        SerializeMap::end(self)
+    }
+}
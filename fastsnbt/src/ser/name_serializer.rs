@@ -1,20 +1,28 @@
-use std::io::Write;
+use alloc::format;
 
 use serde::{ser::Impossible, Serializer};
 
-use crate::error::Error;
+use crate::{error::Error, Dialect, Quoting};
 
-use super::write_escaped_str;
+use super::{write_key, Write};
 
 pub(crate) struct NameSerializer<W: Write> {
     pub(crate) name: W,
+    pub(crate) quoting: Quoting,
+    pub(crate) unquoted_keys: bool,
+    pub(crate) dialect: Dialect,
 }
 
-fn name_must_be_stringy(ty: &str) -> Error {
-    Error::bespoke(format!("field must be string-like, found {ty}"))
+fn name_must_be_stringy(ty: &'static str) -> Error {
+    Error::unsupported_type(
+        ty,
+        format!("field must be string-like, a number or a bool, found {ty}"),
+    )
 }
 
-/// NameSerializer is all about serializing the name of a field. It does not
+/// NameSerializer is all about serializing the name of a field. Numbers and
+/// bools are written as the string of their value, so maps keyed by them can
+/// be serialized. It does not
 /// write the length or the tag. We typically need to write this to a different
 /// buffer than the main one we're writing to, because we need to write out the
 /// field in tag, name, value order. In order the write the tag we need to know
@@ -32,48 +40,56 @@ impl<W: Write> Serializer for &mut NameSerializer<W> {
     type SerializeStruct = Impossible<(), Error>;
     type SerializeStructVariant = Impossible<(), Error>;
 
-    fn serialize_bool(self, _: bool) -> Result<Self::Ok, Self::Error> {
-        Err(name_must_be_stringy("bool"))
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
+        self.serialize_str(if v { "true" } else { "false" })
     }
 
-    fn serialize_i8(self, _: i8) -> Result<Self::Ok, Self::Error> {
-        Err(name_must_be_stringy("i8"))
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
+        self.serialize_str(itoa::Buffer::new().format(v))
     }
 
-    fn serialize_i16(self, _: i16) -> Result<Self::Ok, Self::Error> {
-        Err(name_must_be_stringy("i16"))
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
+        self.serialize_str(itoa::Buffer::new().format(v))
     }
 
-    fn serialize_i32(self, _: i32) -> Result<Self::Ok, Self::Error> {
-        Err(name_must_be_stringy("i32"))
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
+        self.serialize_str(itoa::Buffer::new().format(v))
     }
 
-    fn serialize_i64(self, _: i64) -> Result<Self::Ok, Self::Error> {
-        Err(name_must_be_stringy("i64"))
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
+        self.serialize_str(itoa::Buffer::new().format(v))
     }
 
-    fn serialize_u8(self, _: u8) -> Result<Self::Ok, Self::Error> {
-        Err(name_must_be_stringy("u8"))
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
+        self.serialize_str(itoa::Buffer::new().format(v))
     }
 
-    fn serialize_u16(self, _: u16) -> Result<Self::Ok, Self::Error> {
-        Err(name_must_be_stringy("u16"))
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
+        self.serialize_str(itoa::Buffer::new().format(v))
     }
 
-    fn serialize_u32(self, _: u32) -> Result<Self::Ok, Self::Error> {
-        Err(name_must_be_stringy("u32"))
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
+        self.serialize_str(itoa::Buffer::new().format(v))
     }
 
-    fn serialize_u64(self, _: u64) -> Result<Self::Ok, Self::Error> {
-        Err(name_must_be_stringy("u64"))
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
+        self.serialize_str(itoa::Buffer::new().format(v))
     }
 
-    fn serialize_f32(self, _: f32) -> Result<Self::Ok, Self::Error> {
-        Err(name_must_be_stringy("f32"))
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
+        self.serialize_str(itoa::Buffer::new().format(v))
     }
 
-    fn serialize_f64(self, _: f64) -> Result<Self::Ok, Self::Error> {
-        Err(name_must_be_stringy("f64"))
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
+        self.serialize_str(itoa::Buffer::new().format(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
+        self.serialize_str(ryu::Buffer::new().format(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
+        self.serialize_str(ryu::Buffer::new().format(v))
     }
 
     fn serialize_char(self, c: char) -> Result<Self::Ok, Self::Error> {
@@ -82,7 +98,13 @@ impl<W: Write> Serializer for &mut NameSerializer<W> {
     }
 
     fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
-        write_escaped_str(&mut self.name, v)
+        write_key(
+            &mut self.name,
+            v,
+            self.quoting,
+            self.unquoted_keys,
+            self.dialect,
+        )
     }
 
     fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
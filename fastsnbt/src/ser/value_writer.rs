@@ -0,0 +1,91 @@
+use fastnbt::Value;
+use serde::Serializer as _;
+
+use crate::error::Error;
+
+use super::{write_escaped_str, write_key, Serializer, Write};
+
+impl<W: Write> Serializer<W> {
+    /// Write a [`Value`] by walking it directly, rather than through its
+    /// `Serialize` impl. The output is the same either way, but this avoids
+    /// going through serde for every tag, and the array token dance for arrays.
+    pub(crate) fn write_value(&mut self, value: &Value) -> Result<(), Error> {
+        match value {
+            Value::Byte(v) => self.serialize_i8(*v),
+            Value::Short(v) => self.serialize_i16(*v),
+            Value::Int(v) => self.serialize_i32(*v),
+            Value::Long(v) => self.serialize_i64(*v),
+            Value::Float(v) => self.serialize_f32(*v),
+            Value::Double(v) => self.serialize_f64(*v),
+            Value::String(v) => {
+                write_escaped_str(&mut self.writer, v, self.opts.quoting, self.opts.dialect)
+            }
+            Value::ByteArray(v) => self.write_int_array("B;", v.iter().copied(), Some(b'b')),
+            Value::IntArray(v) => self.write_int_array("I;", v.iter().copied(), None),
+            Value::LongArray(v) => self.write_int_array("L;", v.iter().copied(), Some(b'l')),
+            Value::List(list) => {
+                if list.is_empty() {
+                    return self.writer.write_all(b"[]");
+                }
+                self.writer.write_all(b"[")?;
+                self.push_indent();
+                for (i, v) in list.iter().enumerate() {
+                    if i > 0 {
+                        self.writer.write_all(b",")?;
+                    }
+                    self.newline()?;
+                    self.write_value(v)?;
+                }
+                self.pop_indent();
+                self.newline()?;
+                self.writer.write_all(b"]")
+            }
+            Value::Compound(map) => {
+                let mut entries = map
+                    .iter()
+                    .filter(|(_, v)| !self.is_omitted_value(v))
+                    .map(|(k, v)| {
+                        let mut name = Vec::new();
+                        write_key(
+                            &mut name,
+                            k,
+                            self.opts.quoting,
+                            self.opts.unquoted_keys,
+                            self.opts.dialect,
+                        )?;
+                        Ok((name, v))
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?;
+                if entries.is_empty() {
+                    return self.writer.write_all(b"{}");
+                }
+                if self.opts.sort_keys {
+                    // Sorted by the written key like other compounds, which
+                    // can differ from the order of the map.
+                    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+                }
+
+                self.writer.write_all(b"{")?;
+                self.push_indent();
+                for (i, (name, v)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        self.writer.write_all(b",")?;
+                    }
+                    self.newline()?;
+                    self.writer.write_all(name)?;
+                    self.write_separator()?;
+                    self.write_value(v)?;
+                }
+                self.pop_indent();
+                self.newline()?;
+                self.writer.write_all(b"}")
+            }
+        }
+    }
+
+    /// Whether a compound entry with this value is left out, like
+    /// [`is_omitted`](super::is_omitted) for the `Serialize` impl.
+    fn is_omitted_value(&self, value: &Value) -> bool {
+        self.opts.skip_empty_seqs && matches!(value, Value::List(list) if list.is_empty())
+    }
+}
@@ -0,0 +1,109 @@
+use fastnbt::{nbt, Value};
+
+use crate::{
+    convert::{nbt_to_snbt, snbt_to_nbt},
+    from_str,
+};
+
+const CHUNK_RAW: &[u8] = include_bytes!("../../../fastanvil/resources/chunk.nbt");
+
+#[test]
+fn test_nbt_to_snbt() {
+    let snbt = nbt_to_snbt(CHUNK_RAW).unwrap();
+    let expected: Value = fastnbt::from_bytes(CHUNK_RAW).unwrap();
+    assert_eq!(expected, from_str::<Value>(&snbt).unwrap());
+
+    assert!(nbt_to_snbt(&CHUNK_RAW[..100]).is_err());
+}
+
+#[test]
+fn test_snbt_to_nbt() {
+    let nbt = snbt_to_nbt("{a: 1b, b: 'two'}", "root").unwrap();
+    #[rustfmt::skip]
+    assert_eq!(
+        vec![
+            10, 0, 4, b'r', b'o', b'o', b't',
+            1, 0, 1, b'a', 1,
+            8, 0, 1, b'b', 0, 3, b't', b'w', b'o',
+            0,
+        ],
+        nbt
+    );
+    let value: Value = fastnbt::from_bytes(&nbt).unwrap();
+    assert_eq!(nbt!({"a": 1i8, "b": "two"}), value);
+
+    assert!(snbt_to_nbt("{a:", "").is_err());
+}
+
+#[test]
+fn test_round_trip() {
+    // Compounds come back with their keys sorted, so start from bytes that
+    // have been through a Value once already.
+    let value: Value = fastnbt::from_bytes(CHUNK_RAW).unwrap();
+    let nbt = fastnbt::to_bytes(&value).unwrap();
+    assert_eq!(nbt, snbt_to_nbt(&nbt_to_snbt(&nbt).unwrap(), "").unwrap());
+}
+
+#[test]
+fn test_snbt_to_nbt_arrays() {
+    let nbt = snbt_to_nbt("{b: [B; 1b], i: [I; 2], l: [L; 3l]}", "").unwrap();
+    #[rustfmt::skip]
+    assert_eq!(
+        vec![
+            10, 0, 0,
+            7, 0, 1, b'b', 0, 0, 0, 1, 1,
+            11, 0, 1, b'i', 0, 0, 0, 1, 0, 0, 0, 2,
+            12, 0, 1, b'l', 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 3,
+            0,
+        ],
+        nbt
+    );
+}
+
+#[test]
+fn test_snbt_to_nbt_needs_compound() {
+    assert!(snbt_to_nbt("[1, 2]", "").is_err());
+    assert!(snbt_to_nbt("1", "").is_err());
+}
+
+#[test]
+fn test_nbt_to_snbt_lone_surrogate() {
+    // {s: "\ud800"}, with the surrogate encoded as modified UTF-8.
+    let nbt = [10, 0, 0, 8, 0, 1, b's', 0, 3, 0xED, 0xA0, 0x80, 0];
+    assert!(nbt_to_snbt(&nbt).is_err());
+
+    let nbt = [10, 0, 0, 8, 0, 1, b's', 0, 3, 0xEF, 0xBF, 0xBD, 0];
+    assert_eq!("{\"s\":\"\u{FFFD}\"}", nbt_to_snbt(&nbt).unwrap());
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+fn test_json_text_component() {
+    use crate::convert::{json_to_snbt, snbt_to_json};
+    use serde_json::json;
+
+    let json = json!({
+        "text": "Hello ",
+        "bold": true,
+        "extra": [
+            {"text": "world", "color": "red", "italic": false},
+            {"translate": "chat.type.text", "with": ["1", 2, 3.5, 5_000_000_000i64]},
+        ],
+    });
+    let snbt = json_to_snbt(&json).unwrap();
+    assert_eq!(
+        "{\"bold\":1b,\"extra\":[{\"color\":\"red\",\"italic\":0b,\"text\":\"world\"},\
+         {\"translate\":\"chat.type.text\",\"with\":[\"1\",2,3.5,5000000000l]}],\"text\":\"Hello \"}",
+        snbt
+    );
+
+    // Back again, but the bools are now numbers.
+    let mut expected = json.clone();
+    expected["bold"] = json!(1);
+    expected["extra"][0]["italic"] = json!(0);
+    assert_eq!(expected, snbt_to_json(&snbt).unwrap());
+
+    assert!(json_to_snbt(&json!({"a": null})).is_err());
+    assert!(snbt_to_json("[NaNd]").is_err());
+    assert_eq!(json!([1, 2]), snbt_to_json("[L; 1l, 2l]").unwrap());
+}
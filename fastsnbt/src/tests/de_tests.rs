@@ -1,7 +1,14 @@
-use fastnbt::{ByteArray, IntArray, LongArray};
-use serde::Deserialize;
+use std::{borrow::Cow, collections::HashMap};
 
-use crate::from_str;
+use fastnbt::{nbt, ByteArray, IntArray, LongArray, Value};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    from_reader, from_reader_with_opts, from_slice, from_slice_with_opts, from_str,
+    from_str_with_opts, list_iter, list_iter_with_opts, parse_value,
+    parser::{parse_f32, parse_f64, parse_str},
+    to_string, to_string_pretty, validate, validate_with_opts, DeOpts,
+};
 
 #[test]
 fn test_num() {
@@ -30,6 +37,65 @@ fn test_float() {
     let input = "inf";
     let f: f64 = from_str(input).unwrap();
     assert_eq!(f64::INFINITY, f);
+    let input = "-Infinityf";
+    let f: f32 = from_str(input).unwrap();
+    assert_eq!(f32::NEG_INFINITY, f);
+    let input = "[infinity,-inf]";
+    let f: Vec<f64> = from_str(input).unwrap();
+    assert_eq!(vec![f64::INFINITY, f64::NEG_INFINITY], f);
+}
+
+#[test]
+fn test_float_without_digits_on_one_side() {
+    assert_eq!(Ok(("", 0.5)), parse_f32(".5f"));
+    assert_eq!(Ok(("", -0.5)), parse_f32("-.5f"));
+    assert_eq!(Ok(("", 5.0)), parse_f32("5.f"));
+    assert_eq!(Ok(("", 0.5)), parse_f64(".5d"));
+    assert_eq!(Ok(("", -0.5)), parse_f64("-.5D"));
+    assert_eq!(Ok(("", 5.0)), parse_f64("5.d"));
+
+    let v: Value = from_str("[.5f,-.5f,5.f]").unwrap();
+    assert_eq!(nbt!([0.5f32, -0.5f32, 5.0f32]), v);
+    let v: Value = from_str("[.5d,-.5d,5.d,5.]").unwrap();
+    assert_eq!(nbt!([0.5, -0.5, 5.0, 5.0]), v);
+}
+
+#[test]
+fn test_float_like_int() {
+    assert_eq!(Ok(("", 15.0)), parse_f64("15d"));
+    assert_eq!(Ok(("", 0.0)), parse_f64("0d"));
+    assert_eq!(Ok(("", -3.0)), parse_f64("-3D"));
+    assert_eq!(Ok(("", 7.0)), parse_f64("007d"));
+    assert_eq!(Ok(("", 2.0)), parse_f32("2f"));
+    // Without the suffix it is an int.
+    assert!(parse_f64("15").is_err());
+
+    let v: Value = from_str("[15d,0d,-3D]").unwrap();
+    assert_eq!(nbt!([15.0, 0.0, -3.0]), v);
+    let v: Value = from_str("{a:15,b:15d,c:15f,d:15b}").unwrap();
+    assert_eq!(nbt!({"a": 15, "b": 15.0, "c": 15.0f32, "d": 15i8}), v);
+}
+
+#[test]
+fn test_float_exponent_without_point() {
+    assert_eq!(Ok(("", 1000.0)), parse_f64("1e3"));
+    assert_eq!(Ok(("", 2000.0)), parse_f64("2e3"));
+    assert_eq!(Ok(("", 0.01)), parse_f64("1E-2d"));
+    assert_eq!(Ok(("", -0.01)), parse_f64("-1e-2D"));
+    assert_eq!(Ok(("", 50.0)), parse_f32("5e+1f"));
+    assert_eq!(Ok(("", 1000.0)), parse_f32("1e3F"));
+    // A float suffix is still needed for f32.
+    assert!(parse_f32("1e3").is_err());
+    // The exponent needs digits.
+    assert!(parse_f64("1e").is_err());
+    assert!(parse_f64("1e+d").is_err());
+
+    let v: Value = from_str("[1e3,1E-2d,2e3d]").unwrap();
+    assert_eq!(nbt!([1000.0, 0.01, 2000.0]), v);
+    let v: Value = from_str("{a:5e+1f,b:1e3,c:1e3f}").unwrap();
+    assert_eq!(nbt!({"a": 50.0f32, "b": 1000.0, "c": 1000.0f32}), v);
+    let f: f64 = from_str("2e3").unwrap();
+    assert_eq!(2000.0, f);
 }
 
 #[test]
@@ -46,6 +112,23 @@ fn test_str() {
     let input = "\"yet\\\"ano\\\\\\\"ther\"";
     let s: String = from_str(input).unwrap();
     assert_eq!("yet\"ano\\\"ther", s);
+    let input = r#""a\tb\nc\/\'\"""#;
+    let s: String = from_str(input).unwrap();
+    assert_eq!("a\tb\nc/'\"", s);
+    let input = r"'\r\b\f\\'";
+    let s: String = from_str(input).unwrap();
+    assert_eq!("\r\x08\x0c\\", s);
+    assert!(from_str::<String>(r#""unknown \q escape""#).is_err());
+
+    let s: String = from_str(r#""caf\u00e9 \u00E9""#).unwrap();
+    assert_eq!("café é", s);
+    let s: String = from_str(r#""\ud83d\ude00""#).unwrap();
+    assert_eq!("\u{1F600}", s);
+    assert!(from_str::<String>(r#""\uZZZZ""#).is_err());
+    assert!(from_str::<String>(r#""\u00e""#).is_err());
+    assert!(from_str::<String>(r#""\ud83d""#).is_err());
+    assert!(from_str::<String>(r#""\ude00""#).is_err());
+    assert!(from_str::<String>(r#""\ud83d\u0041""#).is_err());
     assert!(from_str::<&str>("\"not closed").is_err());
     assert!(from_str::<&str>("test/").is_err());
 }
@@ -73,6 +156,233 @@ fn test_map() {
     assert_eq!(SimpleStruct { s: "test", x: -10 }, data);
 }
 
+#[test]
+fn test_option() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Optional {
+        a: Option<i32>,
+        b: Option<String>,
+        c: Option<Vec<i8>>,
+    }
+
+    let data: Optional = from_str("{a:1,c:[1b]}").unwrap();
+    assert_eq!(
+        Optional {
+            a: Some(1),
+            b: None,
+            c: Some(vec![1])
+        },
+        data
+    );
+    assert_eq!(Some(5i64), from_str("5l").unwrap());
+}
+
+#[test]
+fn test_uuid() {
+    let uuid = uuid::Uuid::parse_str("123e4567-e89b-12d3-a456-426614174000").unwrap();
+    let snbt = to_string(&uuid).unwrap();
+    assert_eq!("\"123e4567-e89b-12d3-a456-426614174000\"", snbt);
+    assert_eq!(uuid, from_str::<uuid::Uuid>(&snbt).unwrap());
+
+    // Unquoted, this starts like the float 123e4567.
+    let data: HashMap<String, uuid::Uuid> =
+        from_str("{id:123e4567-e89b-12d3-a456-426614174000}").unwrap();
+    assert_eq!(uuid, data["id"]);
+    assert!(from_str::<uuid::Uuid>("not-a-uuid").is_err());
+}
+
+#[test]
+fn test_unquoted_str_starting_like_a_scalar() {
+    // Floats and bools are only read as such when they are the whole token.
+    assert_eq!(
+        nbt!({"a": "nancy", "b": "information", "c": "1.5fx", "d": "true_flag"}),
+        from_str::<Value>("{a: nancy, b: information, c: 1.5fx, d: true_flag}").unwrap()
+    );
+    assert_eq!("1e3d2", from_str::<String>("1e3d2").unwrap());
+    assert_eq!(
+        "invalid type: floating point `1.5`, expected a string at line 1 column 1",
+        from_str::<String>("1.5f").unwrap_err().to_string()
+    );
+    assert!(from_str::<String>("true").is_err());
+}
+
+#[cfg(feature = "uuid")]
+#[test]
+fn test_uuid_int_array() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Entity {
+        #[serde(with = "crate::helpers::uuid_int_array")]
+        uuid: uuid::Uuid,
+    }
+
+    let uuid = uuid::Uuid::parse_str("f81d4fae-7dec-11d0-a765-00a0c91e6bf6").unwrap();
+    let snbt = "{\"uuid\":[I;-132296786,2112623056,-1486552928,-920753162]}";
+    assert_eq!(snbt, to_string(&Entity { uuid }).unwrap());
+    assert_eq!(Entity { uuid }, from_str(snbt).unwrap());
+
+    assert!(from_str::<Entity>("{uuid:[I;1,2,3]}").is_err());
+    assert!(from_str::<Entity>("{uuid:[1,2,3,4]}").is_err());
+}
+
+#[test]
+fn test_epoch_millis() {
+    use std::time::{Duration, SystemTime};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Save {
+        #[serde(with = "crate::helpers::epoch_millis")]
+        time: SystemTime,
+    }
+
+    // 2023-11-14T22:13:20.123Z
+    let time = SystemTime::UNIX_EPOCH + Duration::from_millis(1_700_000_000_123);
+    let snbt = to_string(&Save { time }).unwrap();
+    assert_eq!("{\"time\":1700000000123l}", snbt);
+    assert_eq!(Save { time }, from_str(&snbt).unwrap());
+
+    let before = SystemTime::UNIX_EPOCH - Duration::from_micros(1500);
+    assert_eq!("{\"time\":-2l}", to_string(&Save { time: before }).unwrap());
+    let time = SystemTime::UNIX_EPOCH - Duration::from_millis(2);
+    assert_eq!(Save { time }, from_str("{time:-2l}").unwrap());
+    // Ints widen to longs, as with any i64.
+    assert!(from_str::<Save>("{time:5}").is_ok());
+    assert!(from_str::<Save>("{time:'now'}").is_err());
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn test_chrono_epoch_millis() {
+    use chrono::{DateTime, Utc};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Save {
+        #[serde(with = "crate::helpers::chrono_epoch_millis")]
+        time: DateTime<Utc>,
+    }
+
+    let time: DateTime<Utc> = "2023-11-14T22:13:20.123Z".parse().unwrap();
+    let snbt = to_string(&Save { time }).unwrap();
+    assert_eq!("{\"time\":1700000000123l}", snbt);
+    assert_eq!(Save { time }, from_str(&snbt).unwrap());
+    assert!(from_str::<Save>("{time:9223372036854775807l}").is_err());
+}
+
+#[test]
+fn test_escape_next_to_multibyte() {
+    // An escaped multi-byte character is an unknown escape, reported at the
+    // character rather than panicking on a slice through it.
+    assert!(from_str::<String>("\"\\é\"").is_err());
+    assert!(from_str::<String>("\"a\\\u{1F600}b\"").is_err());
+    let err = crate::parse_str_with_meta("\"\\é\"").unwrap_err();
+    assert_eq!(2, err.position().unwrap().offset);
+    let err = crate::parse_str_with_meta("\"a\\\u{1F600}b\"").unwrap_err();
+    assert_eq!(3, err.position().unwrap().offset);
+
+    let s: String = from_str(r#""é\n😀\\é""#).unwrap();
+    assert_eq!("é\n😀\\é", s);
+    let s: String = from_str(r"'😀\''").unwrap();
+    assert_eq!("😀'", s);
+}
+
+#[test]
+fn test_trailing_backslash() {
+    let message = "trailing backslash / incomplete escape at end of string";
+    for input in ["\"abc\\", "'abc\\", "{a: \"abc\\"] {
+        let err = from_str::<Value>(input).unwrap_err();
+        assert_eq!(&crate::error::ErrorKind::Eof, err.kind(), "{input}");
+        assert!(err.to_string().starts_with(message), "{input}: {err}");
+    }
+    let err = from_str::<String>("\"a\\b\\").unwrap_err();
+    assert!(err.to_string().starts_with(message));
+
+    let err = crate::parse_str_with_meta("\"abc\\").unwrap_err();
+    assert_eq!(format!("{message} at line 1 column 5"), err.to_string());
+    assert_eq!(4, err.position().unwrap().offset);
+
+    // An escaped quote leaves the string unclosed instead.
+    let err = from_str::<String>("\"abc\\\"").unwrap_err();
+    assert!(!err.to_string().starts_with(message));
+}
+
+#[test]
+fn test_str_with_meta() {
+    use crate::{parse_str_with_meta, QuoteStyle};
+
+    let (s, style, rest) = parse_str_with_meta("foo,").unwrap();
+    assert_eq!(
+        ("foo", QuoteStyle::Unquoted, ","),
+        (s.as_ref(), style, rest)
+    );
+    let (s, style, _) = parse_str_with_meta("  'a\"b'").unwrap();
+    assert_eq!(("a\"b", QuoteStyle::Single), (s.as_ref(), style));
+    let (s, style, _) = parse_str_with_meta(r#""a\"b""#).unwrap();
+    assert!(matches!(s, Cow::Owned(_)));
+    assert_eq!(("a\"b", QuoteStyle::Double), (s.as_ref(), style));
+
+    let err = parse_str_with_meta("  [1]").unwrap_err();
+    assert_eq!(2, err.position().unwrap().offset);
+    let err = parse_str_with_meta(r#""bad \q""#).unwrap_err();
+    assert_eq!(6, err.position().unwrap().offset);
+}
+
+#[test]
+fn test_other_quote_inside_str() {
+    use crate::parse_str_with_meta;
+
+    assert_eq!("it's fine", from_str::<&str>(r#""it's fine""#).unwrap());
+    assert_eq!(r#"say "hi""#, from_str::<&str>(r#"'say "hi"'"#).unwrap());
+
+    // Neither needs an escape, so both borrow from the input.
+    let (s, _, _) = parse_str_with_meta(r#""it's fine""#).unwrap();
+    assert!(matches!(s, Cow::Borrowed("it's fine")));
+    let (s, _, _) = parse_str_with_meta(r#"'say "hi"'"#).unwrap();
+    assert!(matches!(s, Cow::Borrowed(r#"say "hi""#)));
+
+    // An escape of the other quote is allowed too, but has to be owned.
+    let (s, _, _) = parse_str_with_meta(r#""it\'s \"x\"""#).unwrap();
+    assert!(matches!(s, Cow::Owned(_)));
+    assert_eq!(r#"it's "x""#, s);
+}
+
+#[test]
+fn test_is_simple_string() {
+    use crate::is_simple_string;
+
+    assert!(is_simple_string("a.b-c_d+e"));
+    assert!(is_simple_string("123"));
+    assert!(is_simple_string("true"));
+    assert!(!is_simple_string(""));
+    assert!(!is_simple_string("a b"));
+    assert!(!is_simple_string("a=b"));
+    assert!(!is_simple_string("a:b"));
+    assert!(!is_simple_string("café"));
+    assert!(!is_simple_string("'a'"));
+
+    // Agrees with what is read back unquoted.
+    for s in ["a.b-c_d+e", "a b", "a=b", "café", "x,y"] {
+        let whole = matches!(parse_str(s), Ok(("", _)));
+        assert_eq!(whole, is_simple_string(s), "{s}");
+    }
+}
+
+#[test]
+fn test_str_expected() {
+    let s: String = from_str("'single'").unwrap();
+    assert_eq!("single", s);
+    // Numbers and bools are not strings, unless they are keys.
+    let err = from_str::<&str>(" 1.5f ").unwrap_err();
+    assert!(
+        err.to_string().contains("expected a borrowed string"),
+        "{err}"
+    );
+    assert!(from_str::<String>("true").is_err());
+    assert!(from_str::<String>("300b").is_err());
+    let map: HashMap<String, i32> = from_str("{1.5f: 1, true: 2}").unwrap();
+    assert_eq!(HashMap::from([("1.5f".into(), 1), ("true".into(), 2)]), map);
+    let err = from_str::<String>("[1]").unwrap_err();
+    assert!(err.to_string().contains("expected a string"), "{err}");
+}
+
 #[test]
 fn test_bytearray() {
     let input = "[B;1b,-2b,3B]";
@@ -80,6 +390,32 @@ fn test_bytearray() {
     assert_eq!(ByteArray::new(vec![1, -2, 3]), data);
 }
 
+#[test]
+fn test_bytearray_as_bytes() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Data {
+        #[serde(with = "serde_bytes")]
+        data: Vec<u8>,
+    }
+
+    let data: Data = from_str("{data: [ B; 1b, -2b, 127B ]}").unwrap();
+    assert_eq!(vec![1, 254, 127], data.data);
+    let bytes: serde_bytes::ByteBuf = from_str("[B;]").unwrap();
+    assert!(bytes.is_empty());
+    // A list of bytes, such as serialize_bytes writes, works too.
+    let bytes: serde_bytes::ByteBuf = from_str("[1b,2b]").unwrap();
+    assert_eq!(&[1, 2], bytes.as_slice());
+    let bytes = serde_bytes::ByteBuf::from(vec![3, 4]);
+    assert_eq!(
+        bytes,
+        from_str::<serde_bytes::ByteBuf>(&to_string(&bytes).unwrap()).unwrap()
+    );
+
+    assert!(from_str::<serde_bytes::ByteBuf>("[B;1b,300]").is_err());
+    assert!(from_str::<serde_bytes::ByteBuf>("[B;1b").is_err());
+    assert!(from_str::<serde_bytes::ByteBuf>("[I;1]").is_err());
+}
+
 #[test]
 fn test_intarray() {
     let input = "[I;1,2,-3]";
@@ -94,6 +430,56 @@ fn test_longarray() {
     assert_eq!(LongArray::new(vec![1, 2, -3]), data);
 }
 
+#[test]
+fn test_array_element_suffix() {
+    use crate::error::ErrorKind;
+    use fastnbt::Tag;
+
+    // Elements may leave out their suffix, but not have the wrong one.
+    assert_eq!(nbt!([B; 1, 2]), from_str::<Value>("[B;1,2b]").unwrap());
+    assert_eq!(nbt!([L; 1, 2]), from_str::<Value>("[L;1,2l]").unwrap());
+    assert_eq!(
+        nbt!([L; 3000000000, -3000000000, 1]),
+        from_str::<Value>("[L;3000000000, -3000000000l ,1]").unwrap()
+    );
+    assert_eq!(
+        LongArray::new(vec![i64::MAX]),
+        from_str::<LongArray>("[L;9223372036854775807]").unwrap()
+    );
+    assert_eq!(
+        [3000000000, 2],
+        from_str::<[i64; 2]>("[L;3000000000,2]").unwrap()
+    );
+    assert!(from_str::<Value>("[L;9223372036854775808]").is_err());
+    assert!(from_str::<Value>("[I;3000000000]").is_err());
+    for (input, index, expected, found, offset) in [
+        ("[B;1,2s]", 1, Tag::Byte, Tag::Short, 5),
+        ("[B; 1b, 2l]", 1, Tag::Byte, Tag::Long, 8),
+        ("[I;1b,2]", 0, Tag::Int, Tag::Byte, 3),
+        ("[I; 1, 2, 3l]", 2, Tag::Int, Tag::Long, 10),
+        ("[L;1l,2s]", 1, Tag::Long, Tag::Short, 6),
+        ("[L;1b]", 0, Tag::Long, Tag::Byte, 3),
+        // Too large for a byte as well, but the suffix is the problem.
+        ("[B; 1, 300s]", 1, Tag::Byte, Tag::Short, 7),
+    ] {
+        let err = from_str::<Value>(input).unwrap_err();
+        assert_eq!(
+            &ErrorKind::MixedArray {
+                index,
+                expected,
+                found
+            },
+            err.kind(),
+            "{input}"
+        );
+        assert_eq!(offset, err.position().unwrap().offset, "{input}");
+    }
+    assert!(from_str::<IntArray>("[I;1,2s]").is_err());
+    assert!(from_str::<LongArray>("[L;1,2b]").is_err());
+    assert!(from_str::<[i64; 2]>("[L;1,2b]").is_err());
+    assert!(from_str::<serde_bytes::ByteBuf>("[B;1,2s]").is_err());
+}
+
 #[test]
 fn test_whitespaced_longarray() {
     let input = " \n\t[   L;
@@ -102,3 +488,1126 @@ fn test_whitespaced_longarray() {
     let data: LongArray = from_str(input).unwrap();
     assert_eq!(LongArray::new(vec![1, 2, -3]), data);
 }
+
+#[test]
+fn test_struct_variant() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum E {
+        Unit,
+        V { a: i32, b: String },
+    }
+
+    let input = "{V:{a:1,b:test}}";
+    let data: E = from_str(input).unwrap();
+    assert_eq!(
+        E::V {
+            a: 1,
+            b: "test".into()
+        },
+        data
+    );
+
+    let data = vec![
+        E::Unit,
+        E::V {
+            a: -5,
+            b: "x".into(),
+        },
+    ];
+    let snbt = to_string_pretty(&data).unwrap();
+    let de: Vec<E> = from_str(&snbt).unwrap();
+    assert_eq!(data, de);
+}
+
+#[test]
+fn test_tuple_variant() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Shape {
+        Rect(i32, i32),
+        Point,
+    }
+
+    let data = vec![Shape::Rect(1, 2), Shape::Point, Shape::Rect(-3, 4)];
+    let snbt = to_string(&data).unwrap();
+    let de: Vec<Shape> = from_str(&snbt).unwrap();
+    assert_eq!(data, de);
+    let snbt = to_string_pretty(&data).unwrap();
+    let de: Vec<Shape> = from_str(&snbt).unwrap();
+    assert_eq!(data, de);
+}
+
+#[test]
+fn test_newtype_variant() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum E {
+        N(i32),
+        S(String),
+        L(Vec<i64>),
+    }
+
+    let data = vec![E::N(1), E::S("text".into()), E::L(vec![1, 2])];
+    let snbt = to_string(&data).unwrap();
+    let de: Vec<E> = from_str(&snbt).unwrap();
+    assert_eq!(data, de);
+    let de: E = from_str("{ N : 5 }").unwrap();
+    assert_eq!(E::N(5), de);
+}
+
+#[test]
+fn test_whitespace_between_tokens() {
+    let input = "{
+    a : [ 1 , 2 ] ,
+    b:{ c : 1 } ,
+    \"d\" :[ B; ],
+    e: [I; 1 , 2 ]
+}";
+    let data: Value = from_str(input).unwrap();
+    assert_eq!(
+        nbt!({
+            "a": [1, 2],
+            "b": {"c": 1},
+            "d": [B;],
+            "e": [I; 1, 2],
+        }),
+        data
+    );
+
+    let data: Vec<Vec<i32>> = from_str("[ [ ] , [ 1 ] ]").unwrap();
+    assert_eq!(vec![vec![], vec![1]], data);
+}
+
+#[test]
+fn test_empty_collections() {
+    for input in ["{}", "{ }", " {\n\t} "] {
+        let v: Value = from_str(input).unwrap();
+        assert_eq!(nbt!({}), v, "{input:?}");
+    }
+    for input in ["[]", "[ ]", " [\n\t] "] {
+        let v: Value = from_str(input).unwrap();
+        assert_eq!(Value::List(vec![]), v, "{input:?}");
+    }
+    let v: Value = from_str("{a:[ ],b:{ }}").unwrap();
+    assert_eq!(nbt!({"a": [], "b": {}}), v);
+
+    assert!(from_str::<Value>("{,}").is_err());
+    assert!(from_str::<Value>("[,]").is_err());
+}
+
+#[test]
+fn test_list_iter() {
+    let items: Vec<i64> = list_iter(" [1, 2l, 3 ] ").map(|r| r.unwrap()).collect();
+    assert_eq!(vec![1, 2, 3], items);
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Block<'a> {
+        name: &'a str,
+    }
+    let mut iter = list_iter::<Block>("[{name:stone},{name:'dirt'}]");
+    assert_eq!(Some(Ok(Block { name: "stone" })), iter.next());
+    assert_eq!(Some(Ok(Block { name: "dirt" })), iter.next());
+    assert_eq!(None, iter.next());
+
+    assert_eq!(0, list_iter::<i32>("[]").count());
+
+    // Errors end the iteration.
+    let mut iter = list_iter::<i32>("[1, x, 3]");
+    assert_eq!(Some(Ok(1)), iter.next());
+    let err = iter.next().unwrap().unwrap_err();
+    assert_eq!(Some(4), err.position().map(|p| p.offset));
+    assert!(iter.next().is_none());
+
+    let results: Vec<_> = list_iter::<i32>("[1] 2").collect();
+    assert_eq!(2, results.len());
+    assert!(results[1].is_err());
+
+    for input in ["", "{}", "1", "[I;1,2]", "[1, 2"] {
+        let results: Vec<_> = list_iter::<i32>(input).collect();
+        assert!(results.last().unwrap().is_err(), "{input:?}");
+    }
+
+    let opts = DeOpts::new().strict_lists(true);
+    let results: Vec<_> = list_iter_with_opts::<i64>("[1, 2l]", opts).collect();
+    assert!(results[1].is_err());
+}
+
+#[test]
+fn test_pretty_round_trip() {
+    let data = nbt!({
+        "list": [{"x": 1i8}, {}],
+        "nested": {"bytes": [B; 1, 2], "empty": [L;], "s": "text"},
+    });
+    let snbt = to_string_pretty(&data).unwrap();
+    let de: Value = from_str(&snbt).unwrap();
+    assert_eq!(data, de);
+}
+
+#[test]
+fn test_parse_value() {
+    let (value, rest) = parse_value("{a:1b,b:[L;2l]},{c:3}").unwrap();
+    assert_eq!(nbt!({"a": 1i8, "b": [L; 2]}), value);
+    assert_eq!(",{c:3}", rest);
+
+    let (value, rest) = parse_value("'text' tail").unwrap();
+    assert_eq!(Value::String("text".into()), value);
+    assert_eq!("tail", rest);
+
+    let (value, rest) = parse_value("1.5f").unwrap();
+    assert_eq!(Value::Float(1.5), value);
+    assert_eq!("", rest);
+
+    assert!(parse_value("").is_err());
+    assert!(parse_value("{a:").is_err());
+}
+
+#[test]
+fn test_value_from_snbt() {
+    use crate::ValueExt;
+
+    assert_eq!(
+        nbt!({"a": [1i8, 2i8]}),
+        Value::from_snbt("{a: [1b, 2b]}").unwrap()
+    );
+    // Unlike parse_value, the value must be all of the input.
+    assert!(Value::from_snbt("{a:1b},{c:3}").is_err());
+}
+
+#[test]
+fn test_borrowed_str() {
+    #[derive(Deserialize)]
+    struct Borrowed<'a> {
+        #[serde(borrow)]
+        cow: Cow<'a, str>,
+        #[serde(borrow)]
+        escaped: Cow<'a, str>,
+        s: &'a str,
+        #[serde(borrow)]
+        map: HashMap<&'a str, &'a str>,
+    }
+
+    let input = r#"{cow:"borrowed",escaped:"line\nbreak",s:'single',map:{key:value}}"#;
+    let data: Borrowed = from_str(input).unwrap();
+    assert!(matches!(data.cow, Cow::Borrowed("borrowed")));
+    assert!(matches!(data.escaped, Cow::Owned(ref s) if s == "line\nbreak"));
+    assert_eq!("single", data.s);
+    assert_eq!(Some(&"value"), data.map.get("key"));
+
+    // A &str cannot point into the input if there were escapes to process.
+    assert!(from_str::<&str>(r#""line\nbreak""#).is_err());
+}
+
+#[test]
+fn test_error_position() {
+    let err = from_str::<Value>("{a:1, b:}").unwrap_err();
+    let pos = err.position().unwrap();
+    assert_eq!(8, pos.offset);
+    assert_eq!((1, 9), (pos.line, pos.column));
+    assert_eq!("invalid input at line 1 column 9", err.to_string());
+
+    let err = from_str::<Value>("{\n  a: 1\n  b: 2\n}").unwrap_err();
+    let pos = err.position().unwrap();
+    assert_eq!(11, pos.offset);
+    assert_eq!((3, 3), (pos.line, pos.column));
+
+    let err = from_str::<Value>("{'é':'é' x}").unwrap_err();
+    let pos = err.position().unwrap();
+    assert_eq!(11, pos.offset);
+    assert_eq!((1, 10), (pos.line, pos.column));
+
+    let err = from_str::<i32>("1 2").unwrap_err();
+    assert_eq!(2, err.position().unwrap().offset);
+}
+
+#[test]
+fn test_duplicate_keys() {
+    let input = "{a:1,b:{a:2,c:3},'a':4}";
+    let v: Value = from_str(input).unwrap();
+    assert_eq!(nbt!({"a": 4, "b": {"a": 2, "c": 3}}), v);
+
+    let opts = DeOpts::new().deny_duplicate_keys(true);
+    let err = from_str_with_opts::<Value>(input, opts.clone()).unwrap_err();
+    assert_eq!(
+        "duplicate key in compound: a at line 1 column 18",
+        err.to_string()
+    );
+
+    let v: Value = from_str_with_opts("{a:1,b:{a:2,c:3}}", opts.clone()).unwrap();
+    assert_eq!(nbt!({"a": 1, "b": {"a": 2, "c": 3}}), v);
+
+    #[derive(Debug, Deserialize)]
+    struct S {
+        #[allow(dead_code)]
+        a: i32,
+    }
+    assert!(from_str_with_opts::<S>("{a:1,a:2}", opts).is_err());
+}
+
+#[test]
+fn test_strict_lists() {
+    let input = "[1b, 2s, 3]";
+    let v: Value = from_str(input).unwrap();
+    assert_eq!(
+        Value::List(vec![Value::Byte(1), Value::Short(2), Value::Int(3)]),
+        v
+    );
+
+    let opts = DeOpts::new().strict_lists(true);
+    let err = from_str_with_opts::<Value>(input, opts.clone()).unwrap_err();
+    assert_eq!(
+        "list element at index 1 is short, expected byte like the first element at line 1 column 6",
+        err.to_string()
+    );
+
+    let err = from_str_with_opts::<Value>("[[1, 2], [I; 3]]", opts.clone()).unwrap_err();
+    assert_eq!(9, err.position().unwrap().offset);
+
+    let err =
+        from_str_with_opts::<Value>("[[1], ['a', \"b\"], [B; 1b, 2b]]", opts.clone()).unwrap_err();
+    assert_eq!(18, err.position().unwrap().offset);
+
+    let v: Value = from_str_with_opts("{a: [1b, 2b], b: [[], [1]], c: [I; 1, 2]}", opts).unwrap();
+    assert_eq!(nbt!({"a": [1i8, 2i8], "b": [[], [1]], "c": [I; 1, 2]}), v);
+}
+
+#[test]
+fn test_max_depth() {
+    let input = "[".repeat(10_000) + &"]".repeat(10_000);
+    let err = from_str::<Value>(&input).unwrap_err();
+    assert_eq!(
+        "exceeded max nesting depth of 256 at line 1 column 258",
+        err.to_string()
+    );
+
+    let input = "{a:".repeat(10_000) + "1" + &"}".repeat(10_000);
+    assert!(from_str::<Value>(&input).is_err());
+
+    // Nesting right up to the default limit fits on a test thread's stack.
+    let input = "[".repeat(256) + &"]".repeat(256);
+    assert!(from_str::<Value>(&input).is_ok());
+    let input = "{a:".repeat(256) + "1" + &"}".repeat(256);
+    assert!(from_str::<Value>(&input).is_ok());
+    let input = "[{a:".repeat(128) + "1" + &"}]".repeat(128);
+    assert!(from_str::<Value>(&input).is_ok());
+
+    let nested = |depth: usize| "[".repeat(depth) + &"]".repeat(depth);
+    let opts = DeOpts::new().max_depth(3);
+    assert!(from_str_with_opts::<Value>(&nested(3), opts.clone()).is_ok());
+    assert!(from_str_with_opts::<Value>("[[[I;1]]]", opts.clone()).is_ok());
+    assert!(from_str_with_opts::<Value>("[[], [[]], {a: []}]", opts.clone()).is_ok());
+    assert!(from_str_with_opts::<Value>(&nested(4), opts).is_err());
+}
+
+#[test]
+fn test_trailing_commas() {
+    assert!(from_str::<Value>("{a:1,}").is_err());
+    assert!(from_str::<Value>("[1,2,]").is_err());
+
+    let opts = DeOpts::new().allow_trailing_commas(true);
+    let v: Value = from_str_with_opts("{a:1,}", opts.clone()).unwrap();
+    assert_eq!(nbt!({"a": 1}), v);
+    let v: Value = from_str_with_opts("[1,2,]", opts.clone()).unwrap();
+    assert_eq!(nbt!([1, 2]), v);
+    let v: Value = from_str_with_opts("{a: [I; 1, 2 , ] , b: [{}, ] , }", opts.clone()).unwrap();
+    assert_eq!(nbt!({"a": [I; 1, 2], "b": [{}]}), v);
+
+    assert!(from_str_with_opts::<Value>("[,]", opts.clone()).is_err());
+    assert!(from_str_with_opts::<Value>("{,}", opts.clone()).is_err());
+    assert!(from_str_with_opts::<Value>("[1,,]", opts).is_err());
+}
+
+#[test]
+fn test_radix_integers() {
+    // Without the option these are unquoted strings, as in the game.
+    assert_eq!(Value::from("0x1F"), from_str::<Value>("0x1F").unwrap());
+    assert_eq!(Value::from("0b101"), from_str::<Value>("0b101").unwrap());
+
+    let opts = DeOpts::new().radix_integers(true);
+    let de = |input: &str| from_str_with_opts::<Value>(input, opts.clone());
+    assert_eq!(Value::Int(31), de("0x1F").unwrap());
+    assert_eq!(Value::Int(-31), de("-0X1f").unwrap());
+    assert_eq!(Value::Int(0x1fb), de("0x1Fb").unwrap());
+    assert_eq!(Value::Short(31), de("0x1Fs").unwrap());
+    assert_eq!(Value::Long(i64::MAX), de("0x7FFFFFFFFFFFFFFFL").unwrap());
+    assert_eq!(Value::Byte(5), de("0b101b").unwrap());
+    assert_eq!(Value::Short(-5), de("-0b101S").unwrap());
+    assert_eq!(Value::Int(5), de("+0b101").unwrap());
+    assert_eq!(
+        Value::Long(i64::MIN),
+        de(&format!("-0b1{}l", "0".repeat(63))).unwrap()
+    );
+    assert_eq!(nbt!([I; 16, 1, 10]), de("[I; 0x10, 0b1, 10]").unwrap());
+    assert_eq!(nbt!({"a": 1}), de("{a: 0x1}").unwrap());
+
+    assert_eq!(
+        "value 0xFFFFFFFFFFFFFFFF out of range for long (TAG_Long) at line 1 column 1",
+        de("0xFFFFFFFFFFFFFFFFl").unwrap_err().to_string()
+    );
+    assert_eq!(
+        "value -0xFFFFFFFF out of range for int (TAG_Int) at line 1 column 5",
+        de("[I; -0xFFFFFFFF]").unwrap_err().to_string()
+    );
+    assert_eq!(
+        "value 0b100000000 out of range for byte (TAG_Byte) at line 1 column 5",
+        de("{a: 0b100000000b}").unwrap_err().to_string()
+    );
+    assert_eq!(Value::from("0x1Fg"), de("0x1Fg").unwrap());
+    let err = de(&format!("0x1{}", "0".repeat(32))).unwrap_err();
+    assert_eq!(
+        &crate::error::ErrorKind::OutOfRange {
+            tag: fastnbt::Tag::Int
+        },
+        err.kind()
+    );
+    // Nor is a prefix without digits.
+    assert_eq!(Value::from("0x"), de("0x").unwrap());
+    assert_eq!(Value::from("0b2"), de("0b2").unwrap());
+}
+
+#[test]
+fn test_from_reader() {
+    let input = "{name: 'Steve', pos: [1.5d, 64.0d, -2.0d]}";
+    let v: Value = from_reader(input.as_bytes()).unwrap();
+    assert_eq!(from_str::<Value>(input).unwrap(), v);
+
+    let opts = DeOpts::new().deny_duplicate_keys(true);
+    assert!(from_reader_with_opts::<_, Value>("{a:1,a:2}".as_bytes(), opts).is_err());
+
+    let err = from_reader::<_, Value>(&b"'\xff'"[..]).unwrap_err();
+    assert!(err.to_string().starts_with("io error"));
+}
+
+#[test]
+fn test_from_slice() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Borrowed<'a> {
+        name: &'a str,
+    }
+
+    let input = "{name: 'Steve', pos: [1.5d, 64.0d, -2.0d]}".as_bytes();
+    let v: Value = from_slice(input).unwrap();
+    assert_eq!(
+        from_str::<Value>("{name: 'Steve', pos: [1.5d, 64.0d, -2.0d]}").unwrap(),
+        v
+    );
+    let b: Borrowed = from_slice(b"{name: \"Alex\"}").unwrap();
+    assert_eq!(Borrowed { name: "Alex" }, b);
+
+    let opts = DeOpts::new().deny_duplicate_keys(true);
+    assert!(from_slice_with_opts::<Value>(b"{a:1,a:2}", opts).is_err());
+
+    let err = from_slice::<Value>(b"{a: 1,\n b: '\xffx'}").unwrap_err();
+    assert_eq!(
+        "input is not valid UTF-8: invalid utf-8 sequence of 1 bytes from index 12 \
+         at line 2 column 6",
+        err.to_string()
+    );
+    assert_eq!(12, err.position().unwrap().offset);
+}
+
+#[test]
+fn test_case_insensitive_bools() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Flags {
+        a: bool,
+        b: bool,
+    }
+    let input = "{a: True, b: FALSE}";
+    assert!(from_str::<Flags>(input).is_err());
+    let v: Value = from_str(input).unwrap();
+    assert_eq!(nbt!({"a": "True", "b": "FALSE"}), v);
+
+    let opts = DeOpts::new().case_insensitive_bools(true);
+    let flags: Flags = from_str_with_opts(input, opts.clone()).unwrap();
+    assert_eq!(Flags { a: true, b: false }, flags);
+    let v: Vec<bool> = from_str_with_opts("[tRuE, false]", opts.clone()).unwrap();
+    assert_eq!(vec![true, false], v);
+    let v: Value = from_str_with_opts("{True: 'TRUE'}", opts.clone()).unwrap();
+    assert_eq!(nbt!({"True": "TRUE"}), v);
+
+    // A Value has no bool, so they are bytes, as in the game.
+    let v: Value = from_str_with_opts("[True, FALSE]", opts).unwrap();
+    assert_eq!(nbt!([1i8, 0i8]), v);
+    assert_eq!(Value::Byte(1), from_str::<Value>("true").unwrap());
+}
+
+#[test]
+fn test_plus_sign() {
+    use crate::parser::{parse_i16, parse_i32, parse_i64, parse_i8};
+
+    assert_eq!(Ok(("", 5)), parse_i8("+5b"));
+    assert_eq!(Ok(("", 0)), parse_i8("+0B"));
+    assert_eq!(Ok(("", 300)), parse_i16("+300s"));
+    assert_eq!(Ok(("", 0)), parse_i32("+0"));
+    assert_eq!(Ok(("", 5)), parse_i32("+5"));
+    assert_eq!(Ok(("", 5)), parse_i64("+5L"));
+    assert!(parse_i8("+128b").is_err());
+
+    assert_eq!(Value::Byte(5), from_str::<Value>("+5b").unwrap());
+    assert_eq!(Value::Short(300), from_str::<Value>("+300s").unwrap());
+    assert_eq!(Value::Int(0), from_str::<Value>("+0").unwrap());
+    assert_eq!(Value::Long(5), from_str::<Value>("+5l").unwrap());
+    assert_eq!(
+        nbt!({"b": [B; 5], "i": [I; 1, 2], "l": [L; 3], "list": [1i8, 2i8]}),
+        from_str::<Value>("{b: [B; +5b], i: [I; +1, +2], l: [L; +3l], list: [+1b, +2b]}").unwrap()
+    );
+}
+
+#[test]
+fn test_out_of_range_integers() {
+    let err = from_str::<Value>("128b").unwrap_err();
+    assert_eq!(
+        "value 128 out of range for byte (TAG_Byte) at line 1 column 1",
+        err.to_string()
+    );
+    let err = from_str::<Value>("{a: [-32769s]}").unwrap_err();
+    assert_eq!(
+        "value -32769 out of range for short (TAG_Short) at line 1 column 6",
+        err.to_string()
+    );
+    let err = from_str::<Value>("[L; 9999999999999999999l]").unwrap_err();
+    assert_eq!(
+        "value 9999999999999999999 out of range for long (TAG_Long) at line 1 column 5",
+        err.to_string()
+    );
+
+    assert!(from_str::<Value>("32768s").is_err());
+
+    // Only a whole token is out of range, anything longer is a string.
+    assert_eq!(
+        nbt!({"a": "300bananas"}),
+        from_str::<Value>("{a:300bananas}").unwrap()
+    );
+    assert_eq!(
+        nbt!(["128bx", "32768s_", "9999999999999999999l.0"]),
+        from_str::<Value>("[128bx, 32768s_, 9999999999999999999l.0]").unwrap()
+    );
+
+    assert_eq!(Value::Byte(-128), from_str::<Value>("-128b").unwrap());
+    assert_eq!(Value::Short(32767), from_str::<Value>("32767s").unwrap());
+    assert_eq!(
+        Value::Long(i64::MIN),
+        from_str::<Value>("-9223372036854775808L").unwrap()
+    );
+}
+
+#[test]
+fn test_int_without_long_suffix() {
+    let err = from_str::<Value>("99999999999").unwrap_err();
+    assert_eq!(
+        "int literal 99999999999 out of range, did you mean to add 'l'? at line 1 column 1",
+        err.to_string()
+    );
+    let err = from_str::<Value>("{a: -2147483649}").unwrap_err();
+    assert_eq!(
+        "int literal -2147483649 out of range, did you mean to add 'l'? at line 1 column 5",
+        err.to_string()
+    );
+    let err = from_str::<Value>("[I; 99999999999999999999]").unwrap_err();
+    assert_eq!(
+        "value 99999999999999999999 out of range for int (TAG_Int) at line 1 column 5",
+        err.to_string()
+    );
+
+    assert_eq!(
+        Value::Long(99999999999),
+        from_str::<Value>("99999999999l").unwrap()
+    );
+    assert_eq!(
+        Value::Int(i32::MIN),
+        from_str::<Value>("-2147483648").unwrap()
+    );
+
+    // Only a whole token is a number, so these are unquoted strings.
+    assert_eq!(
+        Value::from("99999999999x"),
+        from_str::<Value>("99999999999x").unwrap()
+    );
+    assert_eq!(
+        nbt!({"id": "12345678901_old", "n": "-99999999999-a"}),
+        from_str::<Value>("{id: 12345678901_old, n: -99999999999-a}").unwrap()
+    );
+    assert_eq!(Value::from("12x"), from_str::<Value>("12x").unwrap());
+}
+
+#[test]
+fn test_list_element_types() {
+    let v: Value = from_str("[1,2,3]").unwrap();
+    assert_eq!(
+        Value::List(vec![Value::Int(1), Value::Int(2), Value::Int(3)]),
+        v
+    );
+    let v: Value = from_str("[1b,2B]").unwrap();
+    assert_eq!(Value::List(vec![Value::Byte(1), Value::Byte(2)]), v);
+    let v: Value = from_str("[1b,2]").unwrap();
+    assert_eq!(Value::List(vec![Value::Byte(1), Value::Int(2)]), v);
+    let v: Value = from_str("[[],{}]").unwrap();
+    assert_eq!(nbt!([[], {}]), v);
+
+    // The first element decides the tag of the list in binary NBT, an empty
+    // list is a list of end tags.
+    let bytes = fastnbt::to_bytes(&from_str::<Value>("{a:[],b:[1b,2b]}").unwrap()).unwrap();
+    #[rustfmt::skip]
+    assert_eq!(
+        vec![
+            10, 0, 0,
+            9, 0, 1, b'a', 0, 0, 0, 0, 0,
+            9, 0, 1, b'b', 1, 0, 0, 0, 2, 1, 2,
+            0,
+        ],
+        bytes
+    );
+}
+
+#[test]
+fn test_error_kind() {
+    use crate::error::ErrorKind;
+    use fastnbt::Tag;
+
+    let kind = |input: &str, opts: DeOpts| from_str_with_opts::<Value>(input, opts).unwrap_err();
+
+    assert_eq!(&ErrorKind::Syntax, kind("{a:1 b:2}", DeOpts::new()).kind());
+    assert_eq!(&ErrorKind::Syntax, kind("1 2", DeOpts::new()).kind());
+    assert_eq!(&ErrorKind::Eof, kind("[1,", DeOpts::new()).kind());
+    assert_eq!(
+        &ErrorKind::OutOfRange { tag: Tag::Byte },
+        kind("300b", DeOpts::new()).kind()
+    );
+    assert_eq!(
+        &ErrorKind::OutOfRange { tag: Tag::Int },
+        kind("99999999999", DeOpts::new()).kind()
+    );
+    assert_eq!(
+        &ErrorKind::TooDeep { max_depth: 1 },
+        kind("[[1]]", DeOpts::new().max_depth(1)).kind()
+    );
+    assert_eq!(
+        &ErrorKind::DuplicateKey { key: "a".into() },
+        kind("{a:1,a:2}", DeOpts::new().deny_duplicate_keys(true)).kind()
+    );
+    assert_eq!(
+        &ErrorKind::MixedList {
+            index: 1,
+            expected: Tag::Byte,
+            found: Tag::Short
+        },
+        kind("[1b,2s]", DeOpts::new().strict_lists(true)).kind()
+    );
+
+    let err = from_str::<i32>("'text'").unwrap_err();
+    assert_eq!(&ErrorKind::Custom, err.kind());
+}
+
+#[cfg(feature = "lossless")]
+#[test]
+fn test_lossless() {
+    use crate::{from_str_lossless, LosslessValue};
+
+    let input = r#"{"a":1.50f,"b":+5,"c":[1b,1B,2.0d,-0.0f],"d":3,"e":[I;+1],"f":'s'}"#;
+    let value = from_str_lossless(input).unwrap();
+    let LosslessValue::Compound(map) = &value else {
+        panic!("expected compound, got {value:?}")
+    };
+    assert_eq!(
+        LosslessValue::Raw {
+            value: Value::Float(1.5),
+            text: "1.50f".into()
+        },
+        map["a"]
+    );
+    assert_eq!(LosslessValue::Value(Value::Int(3)), map["d"]);
+    assert_eq!(LosslessValue::Value(nbt!([I; 1])), map["e"]);
+    assert_eq!(
+        from_str::<Value>(input).unwrap(),
+        value.clone().into_value()
+    );
+
+    // Only the string quoting and the array element change on the way back.
+    assert_eq!(
+        input.replace("'s'", "\"s\"").replace("+1]", "1]"),
+        crate::to_string(&value).unwrap()
+    );
+    #[derive(Serialize)]
+    struct Wrapper {
+        v: LosslessValue,
+    }
+    assert_eq!(
+        r#"{"v":{"a":1.50f}}"#,
+        crate::to_string(&Wrapper {
+            v: from_str_lossless("{a: 1.50f}").unwrap()
+        })
+        .unwrap()
+    );
+
+    // Other formats get the plain values, with their types.
+    assert_eq!(
+        fastnbt::to_bytes(&from_str::<Value>(input).unwrap()).unwrap(),
+        fastnbt::to_bytes(&value).unwrap()
+    );
+    assert_eq!(
+        serde_json::json!([1.5, 5]),
+        serde_json::to_value(from_str_lossless("[1.50d, +5]").unwrap()).unwrap()
+    );
+    assert_eq!(
+        Value::List(vec![Value::Float(1.5)]),
+        fastnbt::to_value(from_str_lossless("[1.50f]").unwrap()).unwrap()
+    );
+    // The text is only written in place of the value it came with.
+    assert_eq!("1.5f", crate::to_string(&1.5f32).unwrap());
+
+    assert!(from_str_lossless("{a:1.50f} x").is_err());
+    assert!(from_str_lossless("[1,").is_err());
+}
+
+#[test]
+fn test_validate() {
+    assert_eq!(Ok(()), validate("{a: [B; 1b], b: [{c: 'd'}], e: 1.5f}"));
+    assert_eq!(Ok(()), validate("  [1, 2]\n"));
+
+    let err = validate("{a: 1}\n  junk").unwrap_err();
+    assert_eq!(9, err.position().unwrap().offset);
+    assert_eq!(Some(2), err.position().map(|p| p.line));
+    assert!(validate("{a: 1,").is_err());
+    assert!(validate("").is_err());
+
+    assert_eq!(Ok(()), validate("{a:1,a:2}"));
+    let opts = DeOpts::new().deny_duplicate_keys(true);
+    assert!(validate_with_opts("{a:1,a:2}", opts).is_err());
+}
+
+#[test]
+fn test_deserialize_any_tokens() {
+    use serde_json::json;
+
+    let v: serde_json::Value = from_str(
+        "{c: {x: 1}, l: [1, 2], b: [B; 1b, -2b], i: [I; 3], L: [L; 4l], \
+         q: \"quoted\", s: 'single', u: unquoted, n: 5, y: 6b, h: 7s, g: 8l, \
+         f: 1.5f, d: 2.5, t: true, F: false}",
+    )
+    .unwrap();
+    assert_eq!(
+        json!({
+            "c": {"x": 1},
+            "l": [1, 2],
+            "b": {"__fastnbt_byte_array": [1, -2]},
+            "i": {"__fastnbt_int_array": [3]},
+            "L": {"__fastnbt_long_array": [4]},
+            "q": "quoted",
+            "s": "single",
+            "u": "unquoted",
+            "n": 5,
+            "y": 6,
+            "h": 7,
+            "g": 8,
+            "f": 1.5,
+            "d": 2.5,
+            "t": true,
+            "F": false,
+        }),
+        v
+    );
+
+    // Quoted, these are strings whatever they look like.
+    let v: serde_json::Value = from_str("['1b', \"true\", '[1]']").unwrap();
+    assert_eq!(json!(["1b", "true", "[1]"]), v);
+    assert!(from_str::<serde_json::Value>("'not closed").is_err());
+
+    // The same input into a Value keeps the NBT types.
+    let v: Value = from_str("[B; 1b, -2b]").unwrap();
+    assert_eq!(nbt!([B; 1, -2]), v);
+}
+
+#[test]
+fn test_flatten() {
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Entity {
+        id: String,
+        #[serde(flatten)]
+        extra: HashMap<String, Value>,
+    }
+
+    let input = "{id: zombie, Health: 20.0f, Tags: [a, b], pos: [I; 1, 2], nested: {x: 1b}}";
+    let entity: Entity = from_str(input).unwrap();
+    assert_eq!("zombie", entity.id);
+    assert_eq!(
+        HashMap::from([
+            ("Health".to_string(), Value::Float(20.0)),
+            ("Tags".to_string(), nbt!(["a", "b"])),
+            ("pos".to_string(), nbt!([I; 1, 2])),
+            ("nested".to_string(), nbt!({"x": 1i8})),
+        ]),
+        entity.extra
+    );
+
+    let snbt = to_string(&entity).unwrap();
+    assert_eq!(entity, from_str(&snbt).unwrap());
+    assert!(from_str::<Entity>("{Health: 20.0f}").is_err());
+}
+
+#[test]
+fn test_internally_tagged_enum() {
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    #[serde(tag = "id")]
+    enum BlockEntity {
+        #[serde(rename = "minecraft:sign")]
+        Sign {
+            #[serde(rename = "Text1")]
+            text: String,
+        },
+        #[serde(rename = "minecraft:chest")]
+        Chest {
+            #[serde(rename = "Items")]
+            items: Vec<Value>,
+            #[serde(rename = "Lock")]
+            lock: Option<String>,
+            pos: IntArray,
+        },
+    }
+
+    let sign: BlockEntity = from_str("{Text1: 'hello', id: \"minecraft:sign\"}").unwrap();
+    assert_eq!(
+        BlockEntity::Sign {
+            text: "hello".into()
+        },
+        sign
+    );
+    let chest: BlockEntity =
+        from_str("{id: 'minecraft:chest', pos: [I; 1, 64, -2], Items: [{Slot: 0b, id: stone}]}")
+            .unwrap();
+    assert_eq!(
+        BlockEntity::Chest {
+            items: vec![nbt!({"Slot": 0i8, "id": "stone"})],
+            lock: None,
+            pos: IntArray::new(vec![1, 64, -2]),
+        },
+        chest
+    );
+
+    let snbt = to_string(&sign).unwrap();
+    assert_eq!("{\"id\":\"minecraft:sign\",\"Text1\":\"hello\"}", snbt);
+    assert_eq!(sign, from_str(&snbt).unwrap());
+
+    assert!(from_str::<BlockEntity>("{id: 'minecraft:furnace'}").is_err());
+    assert!(from_str::<BlockEntity>("{Text1: 'no id'}").is_err());
+}
+
+#[test]
+fn test_max_elements() {
+    use crate::error::ErrorKind;
+
+    let mut input = String::from("[I;");
+    for i in 0..1_000_000 {
+        if i > 0 {
+            input.push(',');
+        }
+        input.push('7');
+    }
+    input.push(']');
+
+    let opts = DeOpts::new().max_elements(Some(1000));
+    let err = from_str_with_opts::<Value>(&input, opts.clone()).unwrap_err();
+    assert_eq!(
+        &ErrorKind::TooManyElements { max_elements: 1000 },
+        err.kind()
+    );
+    assert!(err
+        .to_string()
+        .starts_with("exceeded max of 1000 list and array elements"));
+    assert!(
+        from_str_with_opts::<ByteArray>("[B;1b,2b]", opts.clone().max_elements(Some(1))).is_err()
+    );
+    let v: IntArray = from_str_with_opts("[I;1,2]", opts.max_elements(Some(2))).unwrap();
+    assert_eq!(IntArray::new(vec![1, 2]), v);
+
+    // Elements are counted across every list, however they nest, and a
+    // nested list is an element itself.
+    let opts = DeOpts::new().max_elements(Some(5));
+    assert!(from_str_with_opts::<Value>("[[1,2],[3]]", opts.clone()).is_ok());
+    assert!(from_str_with_opts::<Value>("[[1,2],[3,4]]", opts.clone()).is_err());
+    assert!(from_str_with_opts::<Value>("{a:[1,2],b:[3,4,5]}", opts.clone()).is_ok());
+    assert!(from_str_with_opts::<Value>("{a:[1,2],b:[3,4,5,6]}", opts).is_err());
+}
+
+#[test]
+fn test_fixed_size_array() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Chunk {
+        #[serde(rename = "Heightmap", with = "serde_arrays_256")]
+        heightmap: [i32; 256],
+        pos: [i32; 2],
+        biome: [i8; 3],
+        seeds: [i64; 2],
+    }
+
+    // serde only implements Deserialize for arrays up to 32 long.
+    mod serde_arrays_256 {
+        use serde::de::{self, Deserializer, SeqAccess, Visitor};
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<[i32; 256], D::Error> {
+            struct Array;
+            impl<'de> Visitor<'de> for Array {
+                type Value = [i32; 256];
+
+                fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    f.write_str("256 ints")
+                }
+
+                fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                    let mut out = [0; 256];
+                    for (i, v) in out.iter_mut().enumerate() {
+                        *v = seq
+                            .next_element()?
+                            .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+                    }
+                    Ok(out)
+                }
+            }
+            d.deserialize_tuple(256, Array)
+        }
+    }
+
+    let heights = (0..256)
+        .map(|i| i.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    let input = format!(
+        "{{Heightmap: [I; {heights}], pos: [3, -4], biome: [B; 1b, 2b, 3b], seeds: [L; 5l, 6l]}}"
+    );
+    let chunk: Chunk = from_str(&input).unwrap();
+    assert_eq!(std::array::from_fn(|i| i as i32), chunk.heightmap);
+    assert_eq!([3, -4], chunk.pos);
+    assert_eq!([1, 2, 3], chunk.biome);
+    assert_eq!([5, 6], chunk.seeds);
+
+    assert_eq!([1, 2, 3], from_str::<[i32; 3]>("[I; 1, 2, 3]").unwrap());
+    assert_eq!([1, 2, 3], from_str::<[i32; 3]>("[1, 2, 3]").unwrap());
+    assert_eq!((1, 2), from_str::<(i32, i64)>("[L;1l,2l]").unwrap());
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Pos(i32, i32, i32);
+    assert_eq!(Pos(1, 64, -2), from_str("[I;1,64,-2]").unwrap());
+
+    let err = from_str::<[i32; 3]>("[I;1,2]").unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with("invalid length 2, expected an array of length 3"));
+    let err = from_str::<[i32; 2]>("[I;1,2,3,4]").unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with("invalid length 4, expected 2 elements"),
+        "{err}"
+    );
+    let err = from_str::<[i32; 2]>("[1,2,3]").unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with("invalid length 3, expected 2 elements"),
+        "{err}"
+    );
+    assert!(from_str::<[i32; 2]>("{a:1,b:2}").is_err());
+}
+
+#[test]
+fn test_trailing_input() {
+    use crate::{de::Deserializer, error::ErrorKind, from_str_prefix};
+
+    let err = from_str::<Value>("{a:1}garbage").unwrap_err();
+    assert_eq!(&ErrorKind::Syntax, err.kind());
+    assert_eq!(5, err.position().unwrap().offset);
+    assert!(from_str::<Value>("{a:1} {b:2}").is_err());
+    assert!(from_str::<i32>("1 2").is_err());
+    assert!(from_slice::<Value>(b"[1,2]]").is_err());
+    // Trailing whitespace is fine.
+    assert_eq!(nbt!({"a": 1}), from_str::<Value>("{a:1} \n\t").unwrap());
+
+    let (v, rest): (Value, _) = from_str_prefix("{a:1}garbage").unwrap();
+    assert_eq!((nbt!({"a": 1}), "garbage"), (v, rest));
+    let (v, rest): (Vec<i8>, _) = from_str_prefix("[1b, 2b]  [3b]").unwrap();
+    assert_eq!((vec![1, 2], "[3b]"), (v, rest));
+    let (_, rest): (Value, _) = from_str_prefix("{a:1}  ").unwrap();
+    assert_eq!("", rest);
+
+    let mut des = Deserializer::from_str("[1, 2] x");
+    let v = Vec::<i32>::deserialize(&mut des).unwrap();
+    assert_eq!(vec![1, 2], v);
+    assert_eq!(7, des.end().unwrap_err().position().unwrap().offset);
+}
+
+#[test]
+fn test_dialect() {
+    use crate::Dialect;
+
+    let java = DeOpts::new().dialect(Dialect::Java);
+    let bedrock = DeOpts::new().dialect(Dialect::Bedrock);
+
+    // A leading `+` is only a sign in Java.
+    assert_eq!(5, from_str_with_opts::<i32>("+5", java.clone()).unwrap());
+    assert_eq!(
+        nbt!("+5"),
+        from_str_with_opts::<Value>("+5", bedrock.clone()).unwrap()
+    );
+    assert_eq!(
+        nbt!(["+1b", -1i8]),
+        from_str_with_opts::<Value>("[+1b, -1b]", bedrock.clone()).unwrap()
+    );
+    assert!(from_str_with_opts::<i32>("+5", bedrock.clone()).is_err());
+    assert!(from_str_with_opts::<Value>("[I; +5]", bedrock.clone()).is_err());
+    assert_eq!(
+        -5,
+        from_str_with_opts::<i32>("-5", bedrock.clone()).unwrap()
+    );
+    assert_eq!(
+        0.5,
+        from_str_with_opts::<f64>("5e-1", bedrock.clone()).unwrap()
+    );
+
+    // Bedrock only escapes backslashes and quotes.
+    let input = r#""a\nb\u00e9""#;
+    assert_eq!(
+        "a\nbé",
+        from_str_with_opts::<String>(input, java.clone()).unwrap()
+    );
+    assert!(from_str_with_opts::<String>(input, bedrock.clone()).is_err());
+    assert!(from_str_with_opts::<String>(r"'\t'", bedrock.clone()).is_err());
+    assert_eq!(
+        r#"a\b"c'"#,
+        from_str_with_opts::<String>(r#""a\\b\"c\'""#, bedrock.clone()).unwrap()
+    );
+    assert_eq!(
+        nbt!({"a\\": "\n"}),
+        from_str_with_opts::<Value>("{'a\\\\': \"\n\"}", bedrock.clone()).unwrap()
+    );
+
+    // Unsigned bytes wrap in Bedrock, keeping their bits.
+    assert_eq!(
+        200u8 as i8,
+        from_str_with_opts::<i8>("200b", bedrock.clone()).unwrap()
+    );
+    assert_eq!(
+        nbt!([-56i8, -1i8, 127i8, -128i8]),
+        from_str_with_opts::<Value>("[200b, 255B, 127b, -128b]", bedrock.clone()).unwrap()
+    );
+    assert_eq!(
+        nbt!([B; -56, 0]),
+        from_str_with_opts::<Value>("[B; 200b, 0b]", bedrock.clone()).unwrap()
+    );
+    assert!(from_str_with_opts::<Value>("200b", java).is_err());
+    assert!(from_str_with_opts::<Value>("256b", bedrock.clone()).is_err());
+    assert!(from_str_with_opts::<Value>("-129b", bedrock.clone()).is_err());
+    assert_eq!(
+        Value::from("256bit"),
+        from_str_with_opts::<Value>("256bit", bedrock).unwrap()
+    );
+}
+
+#[test]
+fn test_from_str_with_stats() {
+    use crate::from_str_with_stats;
+    use fastnbt::Tag;
+
+    let input = r#"{
+        name: "Steve",
+        health: 20.0f,
+        pos: [1.5d, 64.0d, -3.25d],
+        flags: {onGround: 1b, fire: -20s},
+        uuid: [I; 1, 2, 3, 4],
+        seen: 1700000000000l,
+        items: [{id: "stone", Count: 1b}, {id: dirt, tags: [B; 1b, 2b]}],
+        empty: [],
+        1: 5
+    }"#;
+    let (value, stats) = from_str_with_stats(input).unwrap();
+    assert_eq!(from_str::<Value>(input).unwrap(), value);
+
+    let counts = [
+        (Tag::End, 0),
+        (Tag::Byte, 2),
+        (Tag::Short, 1),
+        (Tag::Int, 1),
+        (Tag::Long, 1),
+        (Tag::Float, 1),
+        (Tag::Double, 3),
+        (Tag::ByteArray, 1),
+        (Tag::String, 3),
+        (Tag::List, 3),
+        (Tag::Compound, 4),
+        (Tag::IntArray, 1),
+        (Tag::LongArray, 0),
+    ];
+    for (tag, count) in counts {
+        assert_eq!(count, stats.count(tag), "{tag:?}");
+    }
+    assert_eq!(21, stats.total());
+    // The byte array inside a compound inside the items list.
+    assert_eq!(4, stats.max_depth());
+
+    let (_, stats) = from_str_with_stats("5").unwrap();
+    assert_eq!(
+        (1, 1, 0),
+        (stats.count(Tag::Int), stats.total(), stats.max_depth())
+    );
+    assert!(from_str_with_stats("{a:1} x").is_err());
+}
+
+#[test]
+fn test_compound_into_maps() {
+    use crate::{INT_ARRAY_TOKEN, LONG_ARRAY_TOKEN};
+    use std::collections::BTreeMap;
+
+    let input = "{b:1,a:2b,c:[I;1,2],d:{z:1,y:2}}";
+    let map: BTreeMap<String, Value> = from_str(input).unwrap();
+    assert_eq!(vec!["a", "b", "c", "d"], map.keys().collect::<Vec<_>>());
+    assert_eq!(Value::Int(1), map["b"]);
+    assert_eq!(Value::Byte(2), map["a"]);
+    assert_eq!(Value::IntArray(IntArray::new(vec![1, 2])), map["c"]);
+    assert_eq!(nbt!({"z": 1, "y": 2}), map["d"]);
+
+    let map: HashMap<String, Value> = from_str(input).unwrap();
+    assert_eq!(4, map.len());
+    assert_eq!(Value::IntArray(IntArray::new(vec![1, 2])), map["c"]);
+
+    let map: BTreeMap<&str, i64> = from_str("{b:1,a:2b,'c d':3l}").unwrap();
+    assert_eq!(
+        vec![("a", 2), ("b", 1), ("c d", 3)],
+        map.into_iter().collect::<Vec<_>>()
+    );
+    let map: HashMap<i32, String> = from_str("{1:a,-2:b}").unwrap();
+    assert_eq!(HashMap::from([(1, "a".into()), (-2, "b".into())]), map);
+
+    // An array on its own is a map of its token to the elements.
+    let map: BTreeMap<String, Vec<i64>> = from_str("[L;1,2]").unwrap();
+    assert_eq!(BTreeMap::from([(LONG_ARRAY_TOKEN.into(), vec![1, 2])]), map);
+    let map: BTreeMap<String, Value> = from_str("[I;1,2]").unwrap();
+    assert_eq!(nbt!([1, 2]), map[INT_ARRAY_TOKEN]);
+    assert!(from_str::<BTreeMap<String, Value>>("[1,2]").is_err());
+}
+
+#[test]
+fn test_empty_input() {
+    use crate::{error::ErrorKind, from_str_prefix};
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    enum Unit {
+        A,
+    }
+
+    let message = "unexpected end of input: empty sNBT, expected a value";
+    for input in ["", "   ", "\n"] {
+        let errors = [
+            from_str::<Value>(input).unwrap_err(),
+            from_str::<String>(input).unwrap_err(),
+            from_str::<i32>(input).unwrap_err(),
+            from_str::<Unit>(input).unwrap_err(),
+            from_str::<HashMap<String, i32>>(input).unwrap_err(),
+            from_str_prefix::<Value>(input).unwrap_err(),
+            from_slice::<Value>(input.as_bytes()).unwrap_err(),
+            validate(input).unwrap_err(),
+            list_iter::<i32>(input).next().unwrap().unwrap_err(),
+        ];
+        for err in errors {
+            assert_eq!(&ErrorKind::Eof, err.kind(), "{input:?}");
+            assert_eq!(message, err.to_string().split(" at line").next().unwrap());
+            assert_eq!(input.len(), err.position().unwrap().offset);
+        }
+    }
+    let err = from_str::<Value>("\n").unwrap_err();
+    assert_eq!(format!("{message} at line 2 column 1"), err.to_string());
+}
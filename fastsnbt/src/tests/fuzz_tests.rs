@@ -0,0 +1,74 @@
+use fastnbt::Value;
+
+use crate::{from_str, parse_str_with_meta, DeOpts};
+
+/// Inputs that poke at the index arithmetic in the parser, kept alongside the
+/// `deserialize_snbt` fuzz target. Each must be an error or a value, never a
+/// panic.
+
+#[test]
+fn multibyte_after_backslash() {
+    assert!(from_str::<Value>(r#""\é""#).is_err());
+    assert!(from_str::<Value>("'a\\\u{1F600}b'").is_err());
+    assert!(from_str::<Value>(r#""\u00é""#).is_err());
+    assert!(from_str::<Value>(r#""\ud83dé""#).is_err());
+    assert!(parse_str_with_meta(r#""\😀"#).is_err());
+}
+
+#[test]
+fn string_ends_in_escape() {
+    for input in [
+        r#"""#,
+        r#""\"#,
+        r#""\u"#,
+        r#""\u00"#,
+        r#""\ud83d\"#,
+        "'\\'",
+        "{\"é\\",
+    ] {
+        assert!(from_str::<Value>(input).is_err(), "{input}");
+    }
+}
+
+#[test]
+fn numbers_without_digits() {
+    for input in [
+        "", "-", "+", ".", "-.", "e5", "1e", "1e+", ".e1f", "+b", "-l",
+    ] {
+        assert!(from_str::<f64>(input).is_err(), "{input}");
+    }
+    let opts = DeOpts::new().radix_integers(true);
+    for input in ["0x", "-0x", "0b2", "0xs", "0bl"] {
+        assert!(
+            crate::from_str_with_opts::<i64>(input, opts.clone()).is_err(),
+            "{input}"
+        );
+    }
+}
+
+#[test]
+fn unclosed_collections() {
+    for input in [
+        "[",
+        "[B;",
+        "[I;1,",
+        "[L;1l",
+        "{",
+        "{a",
+        "{a:",
+        "{a:1,",
+        "[1,[2,{b:",
+    ] {
+        assert!(from_str::<Value>(input).is_err(), "{input}");
+    }
+}
+
+#[test]
+fn control_characters() {
+    assert_eq!(
+        Value::String("a\0b".into()),
+        from_str::<Value>("'a\0b'").unwrap()
+    );
+    assert!(from_str::<Value>("\0").is_err());
+    assert!(from_str::<Value>("[\u{1F600}]").is_err());
+}
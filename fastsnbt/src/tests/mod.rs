@@ -3,8 +3,12 @@ use serde::{Deserialize, Serialize};
 
 use crate::{from_str, to_string};
 
+mod convert_tests;
 mod de_tests;
+mod fuzz_tests;
+mod round_trip_tests;
 mod ser_tests;
+mod vanilla_tests;
 
 #[test]
 fn test_mixed() {
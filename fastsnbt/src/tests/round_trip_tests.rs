@@ -0,0 +1,236 @@
+use fastnbt::{ByteArray, IntArray, LongArray, Value};
+use proptest::{collection::btree_map, collection::vec, num, prelude::*};
+
+use crate::{
+    from_str, parser::parse_str, ser::write_key, to_string, to_string_pretty, to_string_with_opts,
+    Dialect, Quoting, SerOpts,
+};
+
+fn not_nan_f32() -> num::f32::Any {
+    use num::f32::*;
+    POSITIVE | NEGATIVE | NORMAL | SUBNORMAL | ZERO | INFINITE
+}
+
+fn not_nan_f64() -> num::f64::Any {
+    use num::f64::*;
+    POSITIVE | NEGATIVE | NORMAL | SUBNORMAL | ZERO | INFINITE
+}
+
+/// Keys that may be left unquoted, as well as any other string.
+fn arb_key() -> impl Strategy<Value = String> {
+    prop_oneof![
+        "[a-zA-Z0-9_.+-]{0,8}",
+        "[a-z0-9=:' \"\\\\{}]{0,8}",
+        any::<String>()
+    ]
+}
+
+/// Any `Value`, with nested lists and compounds. NaN is left out since it
+/// never compares equal to itself.
+fn arb_value() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        any::<i8>().prop_map(Value::Byte),
+        any::<i16>().prop_map(Value::Short),
+        any::<i32>().prop_map(Value::Int),
+        any::<i64>().prop_map(Value::Long),
+        not_nan_f32().prop_map(Value::Float),
+        not_nan_f64().prop_map(Value::Double),
+        any::<String>().prop_map(Value::String),
+        vec(any::<i8>(), 0..8).prop_map(|v| Value::ByteArray(ByteArray::new(v))),
+        vec(any::<i32>(), 0..8).prop_map(|v| Value::IntArray(IntArray::new(v))),
+        vec(any::<i64>(), 0..8).prop_map(|v| Value::LongArray(LongArray::new(v))),
+    ];
+    leaf.prop_recursive(4, 64, 8, |inner| {
+        prop_oneof![
+            vec(inner.clone(), 0..8).prop_map(Value::List),
+            btree_map(arb_key(), inner, 0..8).prop_map(Value::Compound),
+        ]
+    })
+}
+
+proptest! {
+    #[test]
+    fn round_trip(v in arb_value()) {
+        let snbt = to_string(&v).unwrap();
+        prop_assert_eq!(&v, &from_str::<Value>(&snbt).unwrap(), "{}", snbt);
+    }
+
+    #[test]
+    fn round_trip_pretty(v in arb_value()) {
+        let snbt = to_string_pretty(&v).unwrap();
+        prop_assert_eq!(&v, &from_str::<Value>(&snbt).unwrap(), "{}", snbt);
+    }
+
+    #[test]
+    fn round_trip_unquoted_keys(v in arb_value()) {
+        let snbt = to_string_with_opts(&v, SerOpts::new().unquoted_keys(true)).unwrap();
+        prop_assert_eq!(&v, &from_str::<Value>(&snbt).unwrap(), "{}", snbt);
+    }
+
+    #[test]
+    fn unquoted_key_reads_back(key in arb_key()) {
+        let mut written = Vec::new();
+        write_key(&mut written, &key, Quoting::Double, true, Dialect::Java).unwrap();
+        let written = String::from_utf8(written).unwrap();
+        let (rest, parsed) = parse_str(&written).unwrap();
+        prop_assert_eq!("", rest);
+        prop_assert_eq!(&key, &parsed);
+        // Only keys that need them get quotes.
+        let quoted = written.starts_with('"');
+        let simple = !key.is_empty()
+            && key.bytes().all(|b| b.is_ascii_alphanumeric() || b"_-.+".contains(&b));
+        prop_assert_eq!(simple, !quoted, "{}", written);
+    }
+
+    #[test]
+    fn output_is_normalized(v in arb_value()) {
+        let snbt = to_string(&v).unwrap();
+        let again = to_string(&from_str::<Value>(&snbt).unwrap()).unwrap();
+        prop_assert_eq!(snbt, again);
+    }
+}
+
+#[cfg(feature = "indexmap")]
+#[test]
+fn compound_keeps_key_order() {
+    use fastnbt::OrderedValue;
+
+    let v: OrderedValue = from_str("{z:1,a:2,m:3}").unwrap();
+    assert_eq!(to_string(&v).unwrap(), r#"{"z":1,"a":2,"m":3}"#);
+
+    let input = r#"{"b":[{"y":1b,"x":[I;1]}],"a":{}}"#;
+    let v: OrderedValue = from_str(input).unwrap();
+    assert_eq!(input, to_string(&v).unwrap());
+
+    // Value still sorts them.
+    let v: Value = from_str("{z:1,a:2,m:3}").unwrap();
+    assert_eq!(to_string(&v).unwrap(), r#"{"a":2,"m":3,"z":1}"#);
+}
+
+#[test]
+fn quoted_keys() {
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Weird {
+        #[serde(rename = "weird:key with space")]
+        weird: i32,
+    }
+
+    let snbt = r#"{"weird:key with space":1}"#;
+    assert_eq!(snbt, to_string(&Weird { weird: 1 }).unwrap());
+    assert_eq!(Weird { weird: 1 }, from_str(snbt).unwrap());
+    assert_eq!(
+        Weird { weird: 1 },
+        from_str("{'weird:key with space': 1}").unwrap()
+    );
+
+    let v: Value = from_str(snbt).unwrap();
+    assert_eq!(fastnbt::nbt!({"weird:key with space": 1}), v);
+    assert_eq!(snbt, to_string(&v).unwrap());
+}
+
+#[test]
+fn top_level_values() {
+    assert_eq!("5", to_string(&5i32).unwrap());
+    assert_eq!(5i32, from_str::<i32>(" 5 ").unwrap());
+    assert_eq!("\"hi\"", to_string("hi").unwrap());
+    assert_eq!("hi", from_str::<String>("hi").unwrap());
+
+    assert_eq!("[1,2,3]", to_string(&vec![1, 2, 3]).unwrap());
+    assert_eq!(vec![1, 2, 3], from_str::<Vec<i32>>("[1,2,3]").unwrap());
+    let v = fastnbt::nbt!([L; 1, 2]);
+    assert_eq!("[L;1l,2l]", to_string(&v).unwrap());
+    assert_eq!(v, from_str::<Value>("[L;1l,2l]").unwrap());
+
+    let v = fastnbt::nbt!({"a": 1i8});
+    assert_eq!("{\"a\":1b}", to_string(&v).unwrap());
+    assert_eq!(v, from_str::<Value>("{a:1b}").unwrap());
+
+    assert!(from_str::<i32>("5 6").is_err());
+}
+
+#[test]
+fn unquoted_keys() {
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Item {
+        id: String,
+        #[serde(rename = "Count")]
+        count: i8,
+        #[serde(rename = "1")]
+        one: i8,
+        #[serde(rename = "a=b")]
+        equals: i32,
+        #[serde(rename = "")]
+        empty: i32,
+    }
+
+    let item = Item {
+        id: "minecraft:stone".into(),
+        count: 1,
+        one: 1,
+        equals: 2,
+        empty: 3,
+    };
+    let opts = SerOpts::new().unquoted_keys(true);
+    let snbt = to_string_with_opts(&item, opts.clone()).unwrap();
+    assert_eq!(r#"{id:"minecraft:stone",Count:1b,1:1b,"a=b":2,"":3}"#, snbt);
+    assert_eq!(item, from_str(&snbt).unwrap());
+
+    // Sorted by the written key, so the quoted keys come first.
+    let v: Value = from_str(&snbt).unwrap();
+    assert_eq!(
+        r#"{"":3,"a=b":2,1:1b,Count:1b,id:"minecraft:stone"}"#,
+        crate::value_to_snbt(&v, opts.sort_keys(true)).unwrap()
+    );
+}
+
+#[test]
+fn float_bits() {
+    let floats = [
+        0.0,
+        -0.0,
+        f32::MIN_POSITIVE,
+        -f32::MIN_POSITIVE,
+        1e-40,
+        f32::from_bits(1),
+        -f32::from_bits(1),
+        f32::MAX,
+        f32::MIN,
+    ];
+    for f in floats {
+        for snbt in [to_string(&f).unwrap(), to_string(&Value::Float(f)).unwrap()] {
+            assert_eq!(
+                f.to_bits(),
+                from_str::<f32>(&snbt).unwrap().to_bits(),
+                "{snbt}"
+            );
+            let Value::Float(v) = from_str(&snbt).unwrap() else {
+                panic!("{snbt} is not a float");
+            };
+            assert_eq!(f.to_bits(), v.to_bits(), "{snbt}");
+        }
+    }
+    assert_eq!("-0.0f", to_string(&-0.0f32).unwrap());
+    for (snbt, f) in [
+        ("-0.0f", -0.0),
+        ("-0f", -0.0),
+        ("-.0f", -0.0),
+        ("1e-40f", 1e-40f32),
+    ] {
+        assert_eq!(
+            f.to_bits(),
+            from_str::<f32>(snbt).unwrap().to_bits(),
+            "{snbt}"
+        );
+    }
+
+    let doubles = [-0.0, f64::MIN_POSITIVE, 1e-310, -f64::from_bits(1)];
+    for f in doubles {
+        let snbt = to_string(&f).unwrap();
+        assert_eq!(
+            f.to_bits(),
+            from_str::<f64>(&snbt).unwrap().to_bits(),
+            "{snbt}"
+        );
+    }
+    assert_eq!("-0.0", to_string(&-0.0f64).unwrap());
+}
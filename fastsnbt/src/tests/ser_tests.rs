@@ -1,6 +1,11 @@
-use crate::{to_string, to_string_pretty};
-use fastnbt::{ByteArray, IntArray, LongArray};
-use serde::Serialize;
+use crate::{
+    error::ErrorKind, from_str, ser::Serializer, to_string, to_string_pretty, to_string_pretty_at,
+    to_string_with_opts, to_vec_with_opts, to_writer, to_writer_pretty, to_writer_with_opts,
+    value_to_snbt, Indent, Quoting, SerOpts, Snbt,
+};
+use fastnbt::{ByteArray, IntArray, LongArray, Value};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt::Write, io::BufWriter};
 
 #[test]
 fn test_true() {
@@ -96,6 +101,45 @@ fn test_normal_array() {
     assert_eq!("{\"bytes\":[0b,1b,2b,3b]}", snbt);
 }
 
+#[test]
+fn test_seq_brackets() {
+    let empty: Vec<i32> = vec![];
+    assert_eq!("[]", to_string(&empty).unwrap());
+    assert_eq!("[]", to_string_pretty(&empty).unwrap());
+    assert_eq!("[5]", to_string(&vec![5]).unwrap());
+    assert_eq!("[\n    5\n]", to_string_pretty(&vec![5]).unwrap());
+    assert_eq!("[1,2]", to_string(&vec![1, 2]).unwrap());
+    assert_eq!("[1b]", to_string(&(1i8,)).unwrap());
+    assert_eq!("[[],[[]]]", to_string(&vec![vec![], vec![empty]]).unwrap());
+    assert_eq!("[I;7]", to_string(&IntArray::new(vec![7])).unwrap());
+}
+
+#[test]
+fn test_dialect() {
+    use crate::{from_str_with_opts, DeOpts, Dialect};
+
+    let text = "a\\b\"c\nd\te\u{1}";
+    let java = SerOpts::new().dialect(Dialect::Java);
+    let bedrock = SerOpts::new().dialect(Dialect::Bedrock);
+    assert_eq!(
+        r#""a\\b\"c\nd\te\u0001""#,
+        to_string_with_opts(text, java).unwrap()
+    );
+    let snbt = to_string_with_opts(text, bedrock.clone()).unwrap();
+    assert_eq!("\"a\\\\b\\\"c\nd\te\u{1}\"", snbt);
+    let opts = DeOpts::new().dialect(Dialect::Bedrock);
+    assert_eq!(text, from_str_with_opts::<String>(&snbt, opts).unwrap());
+
+    // Keys are escaped the same way.
+    let map = HashMap::from([("a\nb", 1)]);
+    assert_eq!(
+        "{\"a\nb\":1}",
+        to_string_with_opts(&map, bedrock.clone()).unwrap()
+    );
+    let value = Value::Compound([("k\n".to_string(), Value::String("v\n".into()))].into());
+    assert_eq!("{\"k\n\":\"v\n\"}", value_to_snbt(&value, bedrock).unwrap());
+}
+
 #[test]
 fn test_bytearray() {
     let data = ByteArray::new(vec![-1, 2, -3, 4]);
@@ -117,6 +161,46 @@ fn test_longarray() {
     assert_eq!("[L;-1l,2l,-3l,4l]", snbt);
 }
 
+#[test]
+fn test_large_long_array() {
+    // Big enough to be written out in several chunks.
+    let longs: Vec<i64> = (0..10_000).map(|i| (i - 5000) * 0x1234_5678_9abc).collect();
+    let array = LongArray::new(longs.clone());
+
+    let elements: Vec<String> = longs.iter().map(|v| format!("{v}l")).collect();
+    let expected = format!("[L;{}]", elements.join(","));
+    assert_eq!(expected, to_string(&array).unwrap());
+
+    let opts = SerOpts::pretty()
+        .indent(Indent::Tabs)
+        .uppercase_suffixes(true);
+    let elements: Vec<String> = longs.iter().map(|v| format!("\n\t\t{v}L")).collect();
+    let expected = format!("{{\n\t\"a\": [\n\t\tL;{}\n\t]\n}}", elements.join(","));
+    let value = Value::Compound([("a".to_string(), Value::LongArray(array.clone()))].into());
+    assert_eq!(expected, value_to_snbt(&value, opts.clone()).unwrap());
+    assert_eq!(expected, to_string_with_opts(&value, opts).unwrap());
+    assert_eq!(
+        array,
+        from_str::<LongArray>(&to_string(&array).unwrap()).unwrap()
+    );
+}
+
+#[test]
+fn test_empty_arrays() {
+    let cases = [
+        (Value::ByteArray(ByteArray::new(vec![])), "[B;]"),
+        (Value::IntArray(IntArray::new(vec![])), "[I;]"),
+        (Value::LongArray(LongArray::new(vec![])), "[L;]"),
+    ];
+    for (value, expected) in cases {
+        assert_eq!(expected, to_string(&value).unwrap());
+        assert_eq!(expected, to_string_pretty(&value).unwrap());
+        assert_eq!(expected, value_to_snbt(&value, SerOpts::new()).unwrap());
+        // Read back as the array, not an empty list.
+        assert_eq!(value, from_str::<Value>(expected).unwrap());
+    }
+}
+
 #[test]
 fn test_struct_arrays() {
     #[derive(Serialize)]
@@ -163,6 +247,40 @@ fn test_struct_arrays_pretty() {
     );
 }
 
+#[test]
+fn test_int_array_pretty() {
+    let data = IntArray::new(vec![1, -2, 3, -4, 5]);
+    let expected = "[
+    I;
+    1,
+    -2,
+    3,
+    -4,
+    5
+]";
+    assert_eq!(expected, to_string_pretty(&data).unwrap());
+    assert_eq!(
+        expected,
+        value_to_snbt(&Value::IntArray(data.clone()), SerOpts::pretty()).unwrap()
+    );
+
+    // Nested arrays are indented with their list, like any other element.
+    let snbt = to_string_pretty(&vec![data]).unwrap();
+    assert_eq!(
+        "[
+    [
+        I;
+        1,
+        -2,
+        3,
+        -4,
+        5
+    ]
+]",
+        snbt
+    );
+}
+
 #[test]
 fn test_struct_arrays_empty_pretty() {
     #[derive(Serialize)]
@@ -187,3 +305,791 @@ fn test_struct_arrays_empty_pretty() {
         snbt
     );
 }
+
+#[test]
+fn test_ambiguous_strings_quoted() {
+    // Each of these would be read back as something other than a string if
+    // it were written bare.
+    for s in ["123", "1.5f", "true", "-0", "", "foo bar"] {
+        let snbt = to_string(s).unwrap();
+        assert_eq!(format!("\"{s}\""), snbt);
+        let value: Value = from_str(&snbt).unwrap();
+        assert_eq!(Value::String(s.to_owned()), value);
+    }
+}
+
+#[test]
+fn test_pretty_indent_width() {
+    #[derive(Serialize)]
+    struct Outer {
+        inner: Inner,
+    }
+
+    #[derive(Serialize)]
+    struct Inner {
+        x: i32,
+    }
+
+    let data = Outer {
+        inner: Inner { x: 1 },
+    };
+    let snbt = to_string_with_opts(&data, SerOpts::pretty().indent(Indent::Spaces(2))).unwrap();
+    assert_eq!(
+        "{
+  \"inner\": {
+    \"x\": 1
+  }
+}",
+        snbt
+    );
+}
+
+#[test]
+fn test_pretty_indent_tabs() {
+    #[derive(Serialize)]
+    struct Outer {
+        inner: Inner,
+        list: Vec<i32>,
+    }
+
+    #[derive(Serialize)]
+    struct Inner {
+        x: i32,
+    }
+
+    let data = Outer {
+        inner: Inner { x: 1 },
+        list: vec![2],
+    };
+    let snbt = to_string_with_opts(&data, SerOpts::pretty().indent(Indent::Tabs)).unwrap();
+    assert_eq!(
+        "{\n\t\"inner\": {\n\t\t\"x\": 1\n\t},\n\t\"list\": [\n\t\t2\n\t]\n}",
+        snbt
+    );
+}
+
+#[test]
+fn test_sort_keys() {
+    #[derive(Serialize)]
+    struct Unsorted {
+        c: i32,
+        a: LongArray,
+        b: Vec<i32>,
+    }
+
+    let data = Unsorted {
+        c: 1,
+        a: LongArray::new(vec![1, 2]),
+        b: vec![3],
+    };
+    let snbt = to_string_with_opts(&data, SerOpts::new().sort_keys(true)).unwrap();
+    assert_eq!("{\"a\":[L;1l,2l],\"b\":[3],\"c\":1}", snbt);
+
+    let snbt = to_string_with_opts(&data, SerOpts::pretty().sort_keys(true)).unwrap();
+    assert_eq!(
+        "{
+    \"a\": [
+        L;
+        1l,
+        2l
+    ],
+    \"b\": [
+        3
+    ],
+    \"c\": 1
+}",
+        snbt
+    );
+}
+
+#[test]
+fn test_sort_keys_map() {
+    let data: HashMap<&str, i32> = [("z", 1), ("b", 2), ("m", 3), ("a", 4)]
+        .into_iter()
+        .collect();
+    let snbt = to_string_with_opts(&data, SerOpts::new().sort_keys(true)).unwrap();
+    assert_eq!("{\"a\":4,\"b\":2,\"m\":3,\"z\":1}", snbt);
+
+    let data: HashMap<&str, i32> = HashMap::new();
+    let snbt = to_string_with_opts(&data, SerOpts::new().sort_keys(true)).unwrap();
+    assert_eq!("{}", snbt);
+}
+
+#[test]
+fn test_quoting() {
+    let string = r#"it's "both""#;
+    let snbt = to_string_with_opts(string, SerOpts::new().quoting(Quoting::Double)).unwrap();
+    assert_eq!(r#""it's \"both\"""#, snbt);
+    let snbt = to_string_with_opts(string, SerOpts::new().quoting(Quoting::Single)).unwrap();
+    assert_eq!(r#"'it\'s "both"'"#, snbt);
+    let snbt = to_string_with_opts(string, SerOpts::new().quoting(Quoting::Minimal)).unwrap();
+    assert_eq!(r#"'it\'s "both"'"#, snbt);
+    let snbt = to_string_with_opts("it's", SerOpts::new().quoting(Quoting::Minimal)).unwrap();
+    assert_eq!(r#""it's""#, snbt);
+
+    for quoting in [Quoting::Double, Quoting::Single, Quoting::Minimal] {
+        let snbt = to_string_with_opts(string, SerOpts::new().quoting(quoting)).unwrap();
+        let value: String = from_str(&snbt).unwrap();
+        assert_eq!(string, value);
+    }
+}
+
+#[test]
+fn test_quoting_keys() {
+    let data: HashMap<&str, i32> = [("key", 1)].into_iter().collect();
+    let snbt = to_string_with_opts(&data, SerOpts::new().quoting(Quoting::Single)).unwrap();
+    assert_eq!("{'key':1}", snbt);
+}
+
+#[test]
+fn test_none_field_skipped() {
+    #[derive(Serialize)]
+    struct Optional {
+        a: Option<i32>,
+        b: Option<i32>,
+        c: Option<i32>,
+    }
+
+    let data = Optional {
+        a: None,
+        b: Some(1),
+        c: None,
+    };
+    let snbt = to_string(&data).unwrap();
+    assert_eq!("{\"b\":1}", snbt);
+
+    let data = Optional {
+        a: None,
+        b: None,
+        c: None,
+    };
+    let snbt = to_string(&data).unwrap();
+    assert_eq!("{}", snbt);
+    let snbt = to_string_pretty(&data).unwrap();
+    assert_eq!("{}", snbt);
+
+    let data: HashMap<&str, Option<i32>> = [("a", None)].into_iter().collect();
+    let snbt = to_string(&data).unwrap();
+    assert_eq!("{}", snbt);
+}
+
+#[test]
+fn test_skip_empty_seqs() {
+    #[derive(Serialize)]
+    struct Lists {
+        empty: Vec<i32>,
+        full: Vec<i32>,
+        nested: Vec<Vec<i32>>,
+        ints: IntArray,
+        some_empty: Option<Vec<i32>>,
+    }
+
+    let data = Lists {
+        empty: vec![],
+        full: vec![1, 2],
+        nested: vec![vec![]],
+        ints: IntArray::new(vec![]),
+        some_empty: Some(vec![]),
+    };
+    assert_eq!(
+        r#"{"empty":[],"full":[1,2],"nested":[[]],"ints":[I;],"some_empty":[]}"#,
+        to_string(&data).unwrap()
+    );
+    let opts = SerOpts::new().skip_empty_seqs(true);
+    assert_eq!(
+        r#"{"full":[1,2],"nested":[[]],"ints":[I;]}"#,
+        to_string_with_opts(&data, opts.clone()).unwrap()
+    );
+    assert_eq!(
+        r#"{"full":[1,2],"ints":[I;],"nested":[[]]}"#,
+        to_string_with_opts(&data, opts.clone().sort_keys(true)).unwrap()
+    );
+
+    // An empty list outside a compound is still written.
+    assert_eq!(
+        "[]",
+        to_string_with_opts(&Vec::<i32>::new(), opts.clone()).unwrap()
+    );
+
+    // As is the list in a newtype variant, which would lose the variant.
+    #[derive(Serialize)]
+    enum E {
+        V(Vec<i32>),
+        S { a: Vec<i32> },
+    }
+    assert_eq!(
+        r#"{"V":[]}"#,
+        to_string_with_opts(&E::V(vec![]), opts.clone()).unwrap()
+    );
+    assert_eq!(
+        r#"{V:[]}"#,
+        to_string_with_opts(&E::V(vec![]), opts.clone().unquoted_keys(true)).unwrap()
+    );
+    assert_eq!(
+        r#"{"S":{}}"#,
+        to_string_with_opts(&E::S { a: vec![] }, opts.clone()).unwrap()
+    );
+    let v = fastnbt::nbt!({"a": [], "b": {"c": []}});
+    assert_eq!(
+        r#"{"b":{}}"#,
+        to_string_with_opts(&v, opts.clone().sort_keys(true)).unwrap()
+    );
+    assert_eq!(
+        r#"{"b":{}}"#,
+        value_to_snbt(&v, opts.clone().sort_keys(true)).unwrap()
+    );
+    assert_eq!(
+        "{\n    \"b\": {}\n}",
+        value_to_snbt(&v, SerOpts::pretty().skip_empty_seqs(true).sort_keys(true)).unwrap()
+    );
+    assert_eq!(
+        r#"{"a":[],"b":{"c":[]}}"#,
+        value_to_snbt(&v, SerOpts::new().sort_keys(true)).unwrap()
+    );
+
+    let mut out = Vec::new();
+    let mut compound = crate::compound_writer_with_opts(&mut out, opts).unwrap();
+    compound.field("a", &Vec::<i32>::new()).unwrap();
+    compound.field("b", &[1]).unwrap();
+    compound.finish().unwrap();
+    assert_eq!(br#"{"b":[1]}"#, out.as_slice());
+}
+
+#[test]
+fn test_none_outside_compound() {
+    assert!(to_string(&None::<i32>).is_err());
+    assert!(to_string(&vec![Some(1), None]).is_err());
+}
+
+#[test]
+fn test_nested_options() {
+    #[derive(Debug, PartialEq, Serialize, serde::Deserialize)]
+    struct Nested {
+        a: Option<Option<i32>>,
+        b: Option<Option<i32>>,
+        c: Option<Option<i32>>,
+    }
+
+    let data = Nested {
+        a: Some(Some(1)),
+        b: Some(None),
+        c: None,
+    };
+    let snbt = to_string(&data).unwrap();
+    assert_eq!("{\"a\":1}", snbt);
+    let de: Nested = from_str(&snbt).unwrap();
+    assert_eq!(Nested { b: None, ..data }, de);
+
+    assert_eq!("1", to_string(&Some(Some(1))).unwrap());
+    assert!(to_string(&Some(None::<i32>)).is_err());
+    assert!(to_string(&vec![Some(Some(1)), Some(None)]).is_err());
+}
+
+#[test]
+fn test_struct_variant() {
+    #[derive(Serialize)]
+    enum E {
+        V { a: i32 },
+    }
+
+    let snbt = to_string(&E::V { a: 1 }).unwrap();
+    assert_eq!("{\"V\":{\"a\":1}}", snbt);
+    let snbt = to_string_pretty(&E::V { a: 1 }).unwrap();
+    assert_eq!(
+        "{
+    \"V\": {
+        \"a\": 1
+    }
+}",
+        snbt
+    );
+}
+
+#[test]
+fn test_tuple_variant() {
+    #[derive(Serialize)]
+    enum Shape {
+        Rect(i32, i32),
+    }
+
+    let snbt = to_string(&Shape::Rect(1, 2)).unwrap();
+    assert_eq!("{\"Rect\":[1,2]}", snbt);
+}
+
+#[test]
+fn test_newtype_variant() {
+    #[derive(Serialize)]
+    struct Inner {
+        x: i8,
+    }
+
+    #[derive(Serialize)]
+    enum E {
+        N(i32),
+        C(Inner),
+        O(Option<i32>),
+    }
+
+    let snbt = to_string(&E::N(1)).unwrap();
+    assert_eq!("{\"N\":1}", snbt);
+    let snbt = to_string(&E::C(Inner { x: 2 })).unwrap();
+    assert_eq!("{\"C\":{\"x\":2b}}", snbt);
+    let snbt = to_string_pretty(&E::C(Inner { x: 2 })).unwrap();
+    assert_eq!(
+        "{
+    \"C\": {
+        \"x\": 2b
+    }
+}",
+        snbt
+    );
+    let snbt = to_string(&E::O(Some(3))).unwrap();
+    assert_eq!("{\"O\":3}", snbt);
+    let err = to_string(&E::O(None)).unwrap_err();
+    assert_eq!(&ErrorKind::UnsupportedType { what: "None" }, err.kind());
+}
+
+#[test]
+fn test_serializer_reuse() {
+    #[derive(Serialize)]
+    struct Pos {
+        x: i32,
+    }
+
+    let mut writer = BufWriter::new(Vec::new());
+    let mut serializer = Serializer::new(&mut writer);
+    Pos { x: 1 }.serialize(&mut serializer).unwrap();
+    Pos { x: 2 }.serialize(&mut serializer).unwrap();
+    let out = writer.into_inner().unwrap();
+    assert_eq!(b"{\"x\":1}{\"x\":2}", out.as_slice());
+
+    let mut serializer = Serializer::pretty(Vec::new());
+    Pos { x: 1 }.serialize(&mut serializer).unwrap();
+    assert_eq!(b"{\n    \"x\": 1\n}", serializer.into_inner().as_slice());
+}
+
+#[test]
+fn test_flush() {
+    let mut writer = BufWriter::new(Vec::new());
+    to_writer(&mut writer, &[1, 2]).unwrap();
+    assert_eq!(b"[1,2]", writer.buffer());
+    assert!(writer.get_ref().is_empty());
+
+    let mut writer = BufWriter::new(Vec::new());
+    to_writer_with_opts(&mut writer, &[1, 2], SerOpts::new().auto_flush(true)).unwrap();
+    assert!(writer.buffer().is_empty());
+    assert_eq!(b"[1,2]", writer.get_ref().as_slice());
+
+    let mut writer = BufWriter::new(Vec::new());
+    let mut serializer = Serializer::new(&mut writer);
+    1i8.serialize(&mut serializer).unwrap();
+    serializer.flush().unwrap();
+    assert_eq!(b"1b", writer.get_ref().as_slice());
+}
+
+#[test]
+fn test_non_finite_float() {
+    assert_eq!("NaNf", to_string(&f32::NAN).unwrap());
+    assert_eq!("Infinityf", to_string(&f32::INFINITY).unwrap());
+    assert_eq!("-Infinityf", to_string(&f32::NEG_INFINITY).unwrap());
+    assert_eq!("NaNd", to_string(&f64::NAN).unwrap());
+    assert_eq!("Infinityd", to_string(&f64::INFINITY).unwrap());
+    assert_eq!("-Infinityd", to_string(&f64::NEG_INFINITY).unwrap());
+
+    for f in [f32::INFINITY, f32::NEG_INFINITY] {
+        let de: Value = from_str(&to_string(&f).unwrap()).unwrap();
+        assert_eq!(Value::Float(f), de);
+    }
+    for f in [f64::INFINITY, f64::NEG_INFINITY] {
+        let de: Value = from_str(&to_string(&f).unwrap()).unwrap();
+        assert_eq!(Value::Double(f), de);
+    }
+    let de: Value = from_str(&to_string(&f32::NAN).unwrap()).unwrap();
+    assert!(matches!(de, Value::Float(f) if f.is_nan()));
+    let de: Value = from_str(&to_string(&f64::NAN).unwrap()).unwrap();
+    assert!(matches!(de, Value::Double(f) if f.is_nan()));
+}
+
+#[test]
+fn test_control_char_escape() {
+    let string = "line1\nline2\tend";
+    let snbt = to_string(string).unwrap();
+    assert_eq!("\"line1\\nline2\\tend\"", snbt);
+    let de: String = from_str(&snbt).unwrap();
+    assert_eq!(string, de);
+
+    let string = "nul\0 esc\x1b";
+    let snbt = to_string(string).unwrap();
+    assert_eq!("\"nul\\u0000 esc\\u001b\"", snbt);
+    let de: String = from_str(&snbt).unwrap();
+    assert_eq!(string, de);
+
+    let string = "\r\x08\x0c";
+    let snbt = to_string(string).unwrap();
+    assert_eq!("\"\\r\\b\\f\"", snbt);
+    let de: String = from_str(&snbt).unwrap();
+    assert_eq!(string, de);
+}
+
+#[test]
+fn test_replacement_char() {
+    let snbt = to_string("a\u{FFFD}b").unwrap();
+    assert_eq!("\"a\u{FFFD}b\"", snbt);
+    assert_eq!("a\u{FFFD}b", from_str::<String>(&snbt).unwrap());
+
+    // A lone surrogate can only reach a Rust string as U+FFFD.
+    let lossy = String::from_utf16_lossy(&[0x61, 0xD800, 0x62]);
+    assert_eq!(snbt, to_string(&lossy).unwrap());
+}
+
+#[test]
+fn test_to_string_pretty_at() {
+    let v = fastnbt::nbt!({"a": 1});
+    assert_eq!(
+        "{\n            \"a\": 1\n        }",
+        to_string_pretty_at(&v, 2).unwrap()
+    );
+    assert_eq!(
+        to_string_pretty(&v).unwrap(),
+        to_string_pretty_at(&v, 0).unwrap()
+    );
+    assert_eq!("1", to_string_pretty_at(&1, 3).unwrap());
+}
+
+#[test]
+fn test_snbt_display() {
+    use crate::ValueExt;
+
+    let v = fastnbt::nbt!({"a": [I; 1, 2], "b": "c", "d": [{"e": 1.5f32}]});
+    assert_eq!(to_string(&v).unwrap(), Snbt(&v).to_string());
+    assert_eq!(to_string(&v).unwrap(), format!("{}", Snbt(&v)));
+    assert_eq!(to_string_pretty(&v).unwrap(), format!("{:#}", Snbt(&v)));
+    assert_eq!(to_string(&v).unwrap(), v.snbt().to_string());
+    assert_eq!(to_string_pretty(&v).unwrap(), format!("{:#}", v.snbt()));
+
+    let mut s = String::new();
+    assert!(write!(s, "{}", Snbt(&None::<i32>)).is_err());
+    assert!(write!(s, "{:#}", Snbt(&None::<i32>)).is_err());
+}
+
+#[test]
+fn test_to_writer() {
+    let v = fastnbt::nbt!({"a": [1, 2], "b": "c"});
+    let mut file = Vec::new();
+    to_writer(&mut file, &v).unwrap();
+    to_writer_pretty(&mut file, &v).unwrap();
+    let expected = to_string(&v).unwrap() + &to_string_pretty(&v).unwrap();
+    assert_eq!(expected.as_bytes(), file.as_slice());
+}
+
+#[test]
+fn test_to_fmt_writer() {
+    let v = fastnbt::nbt!({"a": [1, 2], "b": "c\u{e9}"});
+    let mut out = String::new();
+    crate::to_fmt_writer(&mut out, &v).unwrap();
+    crate::to_fmt_writer_with_opts(&mut out, &v, SerOpts::pretty()).unwrap();
+    let expected = to_string(&v).unwrap() + &to_string_pretty(&v).unwrap();
+    assert_eq!(expected, out);
+
+    let opts = SerOpts::new().max_output_bytes(Some(4));
+    assert!(crate::to_fmt_writer_with_opts(String::new(), &v, opts).is_err());
+}
+
+#[test]
+fn test_uppercase_suffixes() {
+    let opts = SerOpts::new().uppercase_suffixes(true);
+    assert_eq!("1B", to_string_with_opts(&1i8, opts.clone()).unwrap());
+
+    let v = fastnbt::nbt!({
+        "b": 1i8, "s": 2i16, "i": 3, "l": 4i64, "f": 0.5f32, "d": 0.5,
+        "bytes": [B; 1, 2], "longs": [L; 3],
+    });
+    let snbt = to_string_with_opts(&v, opts).unwrap();
+    assert_eq!(
+        "{\"b\":1B,\"bytes\":[B;1B,2B],\"d\":0.5,\"f\":0.5F,\"i\":3,\"l\":4L,\"longs\":[L;3L],\"s\":2S}",
+        snbt
+    );
+    assert_eq!(v, from_str::<Value>(&snbt).unwrap());
+}
+
+#[test]
+fn test_bools_as_bytes() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Flags {
+        flag: bool,
+        other: bool,
+    }
+
+    let flags = Flags {
+        flag: true,
+        other: false,
+    };
+    let opts = SerOpts::new().bools_as_bytes(true).unquoted_keys(true);
+    let snbt = to_string_with_opts(&flags, opts.clone()).unwrap();
+    assert_eq!("{flag:1b,other:0b}", snbt);
+    assert_eq!(flags, from_str(&snbt).unwrap());
+    assert_eq!(
+        "1B",
+        to_string_with_opts(&true, opts.uppercase_suffixes(true)).unwrap()
+    );
+    assert_eq!("true", to_string(&true).unwrap());
+
+    // Map keys are strings either way.
+    let map = HashMap::from([(true, 1)]);
+    let opts = SerOpts::new().bools_as_bytes(true);
+    assert_eq!("{\"true\":1}", to_string_with_opts(&map, opts).unwrap());
+}
+
+#[test]
+fn test_minify() {
+    #[derive(Serialize)]
+    enum E {
+        V { x: i32 },
+    }
+    let v = fastnbt::nbt!({"a": 1, "b": [{"c": [I; 1, 2]}, {}], "d": "with spaces"});
+    for opts in [SerOpts::new(), SerOpts::pretty().indent(Indent::Tabs)] {
+        let snbt = to_string_with_opts(&v, opts.clone().minify(true)).unwrap();
+        assert_eq!(
+            "{\"a\":1,\"b\":[{\"c\":[I;1,2]},{}],\"d\":\"with spaces\"}",
+            snbt
+        );
+        let outside_strings = snbt.replace("with spaces", "");
+        assert!(!outside_strings.bytes().any(|b| b.is_ascii_whitespace()));
+
+        let snbt = to_string_with_opts(&E::V { x: 1 }, opts.minify(true)).unwrap();
+        assert_eq!("{\"V\":{\"x\":1}}", snbt);
+    }
+}
+
+#[test]
+fn test_non_string_keys() {
+    let opts = SerOpts::new().sort_keys(true);
+
+    let map: HashMap<i32, i32> = [(1, 10), (-2, 20)].into_iter().collect();
+    let snbt = to_string_with_opts(&map, opts.clone()).unwrap();
+    assert_eq!("{\"-2\":20,\"1\":10}", snbt);
+
+    let map: HashMap<bool, i32> = [(true, 1), (false, 0)].into_iter().collect();
+    let snbt = to_string_with_opts(&map, opts.clone()).unwrap();
+    assert_eq!("{\"false\":0,\"true\":1}", snbt);
+
+    let map: HashMap<Vec<i32>, i32> = [(vec![1], 1)].into_iter().collect();
+    let err = to_string_with_opts(&map, opts).unwrap_err();
+    assert_eq!(
+        "field must be string-like, a number or a bool, found seq",
+        err.to_string()
+    );
+}
+
+#[test]
+fn test_trailing_newline() {
+    let v = fastnbt::nbt!({"a": [{"b": 1}], "c": {}});
+    for opts in [SerOpts::new(), SerOpts::pretty()] {
+        let snbt = to_string_with_opts(&v, opts.clone().trailing_newline(true)).unwrap();
+        assert!(snbt.ends_with("}\n"));
+        assert_eq!(to_string_with_opts(&v, opts).unwrap() + "\n", snbt);
+    }
+}
+
+#[test]
+fn test_128_bit_integers() {
+    assert_eq!(
+        "\"340282366920938463463374607431768211455\"",
+        to_string(&u128::MAX).unwrap()
+    );
+    assert_eq!(
+        "\"-170141183460469231731687303715884105728\"",
+        to_string(&i128::MIN).unwrap()
+    );
+
+    #[derive(Serialize)]
+    struct Entity {
+        uuid: u128,
+    }
+    let snbt = to_string(&Entity { uuid: u128::MAX }).unwrap();
+    assert_eq!(
+        "{\"uuid\":\"340282366920938463463374607431768211455\"}",
+        snbt
+    );
+    assert_eq!(
+        fastnbt::nbt!({"uuid": "340282366920938463463374607431768211455"}),
+        from_str::<Value>(&snbt).unwrap()
+    );
+
+    let map: HashMap<u128, i32> = [(u128::MAX, 1)].into_iter().collect();
+    assert_eq!(
+        "{\"340282366920938463463374607431768211455\":1}",
+        to_string(&map).unwrap()
+    );
+}
+
+#[test]
+fn test_float_precision() {
+    let opts = SerOpts::new().float_precision(Some(2));
+    assert_eq!("0.10f", to_string_with_opts(&0.1f32, opts.clone()).unwrap());
+    assert_eq!(
+        "-2.57",
+        to_string_with_opts(&-2.567f64, opts.clone()).unwrap()
+    );
+    assert_eq!("NaNf", to_string_with_opts(&f32::NAN, opts).unwrap());
+
+    let opts = SerOpts::new().float_precision(Some(0));
+    assert_eq!("2f", to_string_with_opts(&1.5f32, opts.clone()).unwrap());
+    assert_eq!("2d", to_string_with_opts(&1.5f64, opts).unwrap());
+    assert_eq!(Value::Float(2.0), from_str::<Value>("2f").unwrap());
+    assert_eq!(Value::Double(2.0), from_str::<Value>("2d").unwrap());
+
+    assert_eq!(
+        "0.1f",
+        to_string_with_opts(&0.1f32, SerOpts::new()).unwrap()
+    );
+}
+
+#[test]
+fn test_value_to_snbt() {
+    let v = fastnbt::nbt!({
+        "b": 1i8, "s": 2i16, "i": 3, "l": 4i64, "f": 0.5f32, "d": f64::INFINITY,
+        "str": "a \"quoted\" 'string'\n",
+        "bytes": [B; 1, 2], "ints": [I;], "longs": [L; 3],
+        "list": [[], [1, 2], [{}]], "compound": {"z": {}, "a\\": 1, "a": 2},
+    });
+    let all_opts = [
+        SerOpts::new(),
+        SerOpts::pretty(),
+        SerOpts::pretty().indent(Indent::Tabs).minify(true),
+        SerOpts::new().sort_keys(true).quoting(Quoting::Single),
+        SerOpts::pretty()
+            .quoting(Quoting::Minimal)
+            .uppercase_suffixes(true),
+        SerOpts::new()
+            .float_precision(Some(1))
+            .trailing_newline(true),
+    ];
+    for opts in all_opts {
+        assert_eq!(
+            to_string_with_opts(&v, opts.clone()).unwrap(),
+            value_to_snbt(&v, opts).unwrap()
+        );
+    }
+}
+
+#[test]
+fn test_max_output_bytes() {
+    const LIMIT: usize = 10 << 20;
+    // Each element is at least 21 bytes, so this is over 12 MiB.
+    let v = Value::LongArray(LongArray::new(vec![i64::MIN; 600_000]));
+    let opts = SerOpts::new().max_output_bytes(Some(LIMIT));
+
+    let err = to_vec_with_opts(&v, opts.clone()).unwrap_err();
+    assert_eq!("output exceeded 10485760 bytes", err.to_string());
+    let err = value_to_snbt(&v, opts.clone()).unwrap_err();
+    assert_eq!("output exceeded 10485760 bytes", err.to_string());
+
+    let mut out = Vec::new();
+    assert!(to_writer_with_opts(&mut out, &v, opts.clone()).is_err());
+    assert!(out.len() <= LIMIT);
+
+    // Sorted compounds buffer each entry, which counts towards the limit too.
+    let v = fastnbt::nbt!({"a": v});
+    assert!(to_vec_with_opts(&v, opts.clone().sort_keys(true)).is_err());
+
+    let small = Value::LongArray(LongArray::new(vec![i64::MIN; 1000]));
+    assert_eq!(
+        to_string(&small).unwrap(),
+        value_to_snbt(&small, opts).unwrap()
+    );
+    assert_eq!(&ErrorKind::OutputTooLarge { limit: LIMIT }, err.kind());
+
+    // The buffered entries share the limit, so serializing stops at the
+    // entry that takes them over it, though each would fit on its own.
+    struct Counted<'a>(&'a std::cell::Cell<usize>);
+    impl Serialize for Counted<'_> {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            self.0.set(self.0.get() + 1);
+            serializer.serialize_str(&"x".repeat(60))
+        }
+    }
+    let count = std::cell::Cell::new(0);
+    let map: HashMap<_, _> = (0..10).map(|i| (i.to_string(), Counted(&count))).collect();
+    let small_limit = SerOpts::new().max_output_bytes(Some(100)).sort_keys(true);
+    let err = to_vec_with_opts(&map, small_limit).unwrap_err();
+    assert_eq!(&ErrorKind::OutputTooLarge { limit: 100 }, err.kind());
+    // Each entry is serialized once to check it is not `None`, then again
+    // into the buffer.
+    assert_eq!(2 * 2, count.get());
+}
+
+#[test]
+fn test_error_kind() {
+    #[derive(Serialize)]
+    struct Unit;
+
+    let err = to_string(&()).unwrap_err();
+    assert_eq!(&ErrorKind::UnsupportedType { what: "()" }, err.kind());
+    let err = to_string(&Unit).unwrap_err();
+    assert_eq!(
+        &ErrorKind::UnsupportedType {
+            what: "unit struct"
+        },
+        err.kind()
+    );
+    let err = to_string(&HashMap::from([(vec![1], 1)])).unwrap_err();
+    assert_eq!(&ErrorKind::UnsupportedType { what: "seq" }, err.kind());
+}
+
+#[test]
+fn test_compound_writer() {
+    use crate::{compound_writer, compound_writer_with_opts};
+
+    let mut out = Vec::new();
+    let opts = SerOpts::new().unquoted_keys(true);
+    let mut compound = compound_writer_with_opts(&mut out, opts.clone()).unwrap();
+    compound.field("x", &1).unwrap();
+    compound.field("y", &2).unwrap();
+    compound.finish().unwrap();
+    assert_eq!("{x:1,y:2}", String::from_utf8(out).unwrap());
+
+    // The same as serializing all the fields at once.
+    #[derive(Serialize)]
+    struct Fields {
+        a: Option<i8>,
+        b: Option<i8>,
+        longs: LongArray,
+        nested: HashMap<String, Vec<i32>>,
+    }
+    let fields = Fields {
+        a: None,
+        b: Some(3),
+        longs: LongArray::new(vec![1, 2]),
+        nested: HashMap::from([("list".to_string(), vec![1, 2])]),
+    };
+    for opts in [SerOpts::new(), SerOpts::pretty(), opts] {
+        let mut compound = compound_writer_with_opts(Vec::new(), opts.clone()).unwrap();
+        compound.field("a", &fields.a).unwrap();
+        compound.field("b", &fields.b).unwrap();
+        compound.field("longs", &fields.longs).unwrap();
+        compound.field("nested", &fields.nested).unwrap();
+        let out = compound.finish().unwrap();
+        let expected = to_string_with_opts(&fields, opts).unwrap();
+        assert_eq!(expected, String::from_utf8(out).unwrap());
+    }
+
+    for opts in [SerOpts::new(), SerOpts::pretty()] {
+        let out = compound_writer_with_opts(Vec::new(), opts.clone())
+            .unwrap()
+            .finish()
+            .unwrap();
+        assert_eq!("{}", String::from_utf8(out).unwrap());
+    }
+    let out = compound_writer(Vec::new()).unwrap().finish().unwrap();
+    assert_eq!(b"{}", out.as_slice());
+}
+
+#[test]
+fn test_quoting_arrays() {
+    let opts = SerOpts::new().quoting(Quoting::Single);
+    let snbt = to_string_with_opts(&ByteArray::new(vec![1, -2]), opts.clone()).unwrap();
+    assert_eq!("[B;1b,-2b]", snbt);
+    let snbt = to_string_with_opts(&IntArray::new(vec![1, -2]), opts).unwrap();
+    assert_eq!("[I;1,-2]", snbt);
+}
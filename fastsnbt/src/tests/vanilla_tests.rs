@@ -0,0 +1,137 @@
+//! sNBT as vanilla Minecraft writes it, from `/data get` and structure
+//! files. Each must read, and what we write from it must read back the same.
+
+use fastnbt::{ByteArray, IntArray, LongArray, Value};
+
+use crate::{from_str, to_string, to_string_pretty};
+
+const VILLAGER: &str = include_str!("../../tests/fixtures/villager.snbt");
+const ENCHANTED_SWORD: &str = include_str!("../../tests/fixtures/enchanted_sword.snbt");
+const SIGN: &str = include_str!("../../tests/fixtures/sign.snbt");
+const STRUCTURE: &str = include_str!("../../tests/fixtures/structure.snbt");
+
+const FIXTURES: [&str; 4] = [VILLAGER, ENCHANTED_SWORD, SIGN, STRUCTURE];
+
+fn get<'a>(v: &'a Value, path: &[&str]) -> &'a Value {
+    path.iter().fold(v, |v, key| match v {
+        Value::Compound(c) => c.get(*key).unwrap_or_else(|| panic!("no {key}")),
+        _ => panic!("{key} is not in a compound"),
+    })
+}
+
+fn string(v: &Value) -> &str {
+    match v {
+        Value::String(s) => s,
+        v => panic!("{v:?} is not a string"),
+    }
+}
+
+#[test]
+fn fixtures_round_trip() {
+    for fixture in FIXTURES {
+        let v: Value = from_str(fixture).unwrap();
+        for snbt in [to_string(&v).unwrap(), to_string_pretty(&v).unwrap()] {
+            assert_eq!(v, from_str::<Value>(&snbt).unwrap(), "{snbt}");
+        }
+        let snbt = to_string(&v).unwrap();
+        assert_eq!(snbt, to_string(&from_str::<Value>(&snbt).unwrap()).unwrap());
+    }
+}
+
+#[test]
+fn entity_data() {
+    let v: Value = from_str(VILLAGER).unwrap();
+    assert_eq!(
+        &Value::IntArray(IntArray::new(vec![
+            -1268713280,
+            1548042431,
+            -1529385574,
+            -1211189262
+        ])),
+        get(&v, &["UUID"])
+    );
+    assert_eq!(
+        &Value::IntArray(IntArray::new(vec![-23, 64, 118])),
+        get(
+            &v,
+            &["Brain", "memories", "minecraft:job_site", "value", "pos"]
+        )
+    );
+    assert_eq!(&Value::Long(11907), get(&v, &["LastRestock"]));
+    assert_eq!(&Value::Float(20.0), get(&v, &["Health"]));
+    assert_eq!(&Value::Short(300), get(&v, &["Air"]));
+    assert_eq!(
+        &Value::List(vec![Value::Float(271.78467), Value::Float(0.0)]),
+        get(&v, &["Rotation"])
+    );
+    assert_eq!(&Value::List(vec![]), get(&v, &["Gossips"]));
+
+    let name: serde_json::Value = serde_json::from_str(string(get(&v, &["CustomName"]))).unwrap();
+    assert_eq!("Farmer Joe", name["text"]);
+}
+
+#[test]
+fn item_data() {
+    let v: Value = from_str(ENCHANTED_SWORD).unwrap();
+    assert_eq!(&Value::Byte(1), get(&v, &["Count"]));
+    let Value::List(enchantments) = get(&v, &["tag", "Enchantments"]) else {
+        panic!("Enchantments is not a list");
+    };
+    assert_eq!(3, enchantments.len());
+    assert_eq!(&Value::Short(5), get(&enchantments[0], &["lvl"]));
+
+    // A text component with an escaped single quote inside single quotes.
+    let Value::List(lore) = get(&v, &["tag", "display", "Lore"]) else {
+        panic!("Lore is not a list");
+    };
+    let lore: serde_json::Value = serde_json::from_str(string(&lore[1])).unwrap();
+    assert_eq!("Don't ", lore["extra"][0]["text"]);
+}
+
+#[test]
+fn text_components() {
+    let v: Value = from_str(SIGN).unwrap();
+    let Value::List(messages) = get(&v, &["front_text", "messages"]) else {
+        panic!("messages is not a list");
+    };
+    let messages: Vec<serde_json::Value> = messages
+        .iter()
+        .map(|m| serde_json::from_str(string(m)).unwrap())
+        .collect();
+    assert_eq!("\"Spawn\"", messages[1]["text"]);
+    assert_eq!("block.minecraft.oak_sign", messages[2]["translate"]);
+    assert_eq!("", messages[3]);
+
+    // Written back, the components are still valid JSON inside the string.
+    let snbt = to_string(&v).unwrap();
+    assert!(
+        snbt.contains(r#""{\"text\":\"\\\"Spawn\\\"\",\"bold\":true}""#),
+        "{snbt}"
+    );
+}
+
+#[test]
+fn structure_data() {
+    let v: Value = from_str(STRUCTURE).unwrap();
+    assert_eq!(&Value::Int(3465), get(&v, &["DataVersion"]));
+    assert_eq!(
+        &Value::LongArray(LongArray::new(vec![i64::MIN, 0, i64::MAX])),
+        get(&v, &["heightmap"])
+    );
+    assert_eq!(
+        &Value::ByteArray(ByteArray::new(vec![0, 15, -1])),
+        get(&v, &["light"])
+    );
+
+    let Value::List(blocks) = get(&v, &["blocks"]) else {
+        panic!("blocks is not a list");
+    };
+    let Value::List(items) = get(&blocks[1], &["nbt", "Items"]) else {
+        panic!("Items is not a list");
+    };
+    let Value::List(pages) = get(&items[1], &["tag", "pages"]) else {
+        panic!("pages is not a list");
+    };
+    let page: serde_json::Value = serde_json::from_str(string(&pages[0])).unwrap();
+    assert_eq!("Line one\nLine two", page["text"]);
+}
@@ -0,0 +1,37 @@
+use fastnbt::Value;
+use serde::Serialize;
+
+use crate::{error::Result, Snbt};
+
+/// sNBT methods for [`Value`]. `Value` is defined in `fastnbt`, which does
+/// not depend on this crate, so it cannot implement `FromStr` or `Display` as
+/// sNBT. Bring this trait into scope to parse one with `Value::from_snbt` and
+/// display one with `value.snbt()` instead.
+///
+/// ```
+/// use fastnbt::{nbt, Value};
+/// use fastsnbt::ValueExt;
+///
+/// let v = Value::from_snbt("{a: 1, b: [I; 2, 3]}").unwrap();
+/// assert_eq!(nbt!({"a": 1, "b": [I; 2, 3]}), v);
+/// assert_eq!("{\"a\":1,\"b\":[I;2,3]}", v.snbt().to_string());
+/// ```
+pub trait ValueExt: Sized + Serialize {
+    /// Parse a value from some sNBT data, the same as
+    /// [`from_str`](crate::from_str). All of the input must be the value.
+    fn from_snbt(input: &str) -> Result<Self>;
+
+    /// Display the value as sNBT, compact with `{}` and pretty printed with
+    /// `{:#}`. See [`Snbt`].
+    fn snbt(&self) -> Snbt<'_, Self>;
+}
+
+impl ValueExt for Value {
+    fn from_snbt(input: &str) -> Result<Self> {
+        crate::from_str(input)
+    }
+
+    fn snbt(&self) -> Snbt<'_, Self> {
+        Snbt(self)
+    }
+}
@@ -0,0 +1,90 @@
+//! Serializing without the `std` feature. This is `no_std` itself, so it only
+//! builds against what the crate has without `std`, and it runs with
+//! `cargo test --no-default-features --test no_std` as well as by default.
+#![no_std]
+
+extern crate alloc;
+
+use alloc::{collections::BTreeMap, format, string::String, vec, vec::Vec};
+
+use fastsnbt::{error::Error, ser::Serializer, ser::Write, SerOpts, Snbt};
+use serde::{ser::Error as _, Serialize};
+
+#[derive(Serialize)]
+struct Item<'a> {
+    id: &'a str,
+    count: i8,
+    enchantments: Vec<&'a str>,
+    damage: Option<i16>,
+}
+
+fn sword() -> Item<'static> {
+    Item {
+        id: "minecraft:diamond_sword",
+        count: 1,
+        enchantments: vec!["sharpness"],
+        damage: None,
+    }
+}
+
+/// A writer into a fixed buffer, like one would write for a device without
+/// an allocator to spare.
+struct Buffer {
+    buf: [u8; 32],
+    len: usize,
+}
+
+impl Write for Buffer {
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), Error> {
+        let end = self.len + buf.len();
+        let dest = self
+            .buf
+            .get_mut(self.len..end)
+            .ok_or_else(|| Error::custom("buffer full"))?;
+        dest.copy_from_slice(buf);
+        self.len = end;
+        Ok(())
+    }
+}
+
+#[test]
+fn to_string() {
+    assert_eq!(
+        r#"{"id":"minecraft:diamond_sword","count":1b,"enchantments":["sharpness"]}"#,
+        fastsnbt::to_string(&sword()).unwrap()
+    );
+    let opts = SerOpts::new().unquoted_keys(true).sort_keys(true);
+    assert_eq!(
+        r#"{count:1b,enchantments:["sharpness"],id:"minecraft:diamond_sword"}"#,
+        fastsnbt::to_string_with_opts(&sword(), opts).unwrap()
+    );
+}
+
+#[test]
+fn to_fmt_writer() {
+    let mut out = String::from("item: ");
+    fastsnbt::to_fmt_writer(&mut out, &[1.5f32, 2.0]).unwrap();
+    assert_eq!("item: [1.5f,2.0f]", out);
+    assert_eq!(
+        "{\n    \"a\": 1\n}",
+        format!("{:#}", Snbt(&BTreeMap::from([("a", 1)])))
+    );
+}
+
+#[test]
+fn own_writer() {
+    let mut serializer = Serializer::new(Buffer {
+        buf: [0; 32],
+        len: 0,
+    });
+    (5i64, "five").serialize(&mut serializer).unwrap();
+    let buffer = serializer.into_inner();
+    assert_eq!(b"[5l,\"five\"]", &buffer.buf[..buffer.len]);
+
+    let mut serializer = Serializer::new(Buffer {
+        buf: [0; 32],
+        len: 0,
+    });
+    let err = sword().serialize(&mut serializer).unwrap_err();
+    assert_eq!("buffer full", format!("{err}"));
+}
@@ -0,0 +1,19 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use fastnbt::Value;
+use fastsnbt::error::Result;
+use fastsnbt::{from_str, to_string};
+
+fuzz_target!(|data: &[u8]| {
+    // Only errors are allowed for bad input, never panics.
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
+    let value: Result<Value> = from_str(input);
+    if let Ok(v) = value {
+        // Whatever parses must write out as sNBT that parses again.
+        let snbt = to_string(&v).unwrap();
+        let _: Value = from_str(&snbt).unwrap();
+    }
+});